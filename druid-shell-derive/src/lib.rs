@@ -0,0 +1,63 @@
+//! Build-time asset embedding for [`druid_shell::Icon`](../druid_shell/icon/struct.Icon.html).
+//!
+//! `include_icon!("assets/app.png")` decodes the referenced image at compile time
+//! and expands to an already-built `Icon`, so a missing or malformed icon asset
+//! fails the build instead of failing at startup, and no decoding happens at
+//! runtime.
+
+use std::path::Path;
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{parse_macro_input, LitStr};
+
+/// Decodes an icon file at compile time and embeds the resulting RGBA bytes.
+///
+/// The path is resolved relative to the including crate's `CARGO_MANIFEST_DIR`,
+/// same as [`include_bytes!`]. Expands to an expression of type
+/// `druid_shell::Icon`.
+#[proc_macro]
+pub fn include_icon(input: TokenStream) -> TokenStream {
+    let path_lit = parse_macro_input!(input as LitStr);
+    let path = path_lit.value();
+
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let full_path = Path::new(&manifest_dir).join(&path);
+
+    let image = match image::open(&full_path) {
+        Ok(image) => image,
+        Err(err) => {
+            return syn::Error::new(
+                Span::call_site(),
+                format!("include_icon!: couldn't decode `{}`: {}", path, err),
+            )
+            .to_compile_error()
+            .into();
+        }
+    };
+
+    let rgba = image.to_rgba8();
+    let (width, height) = rgba.dimensions();
+    let bytes = rgba.into_raw();
+    let include_path = full_path.to_string_lossy().into_owned();
+
+    let expanded = quote! {
+        {
+            // Registers the source asset as a build dependency, the same way
+            // `include_bytes!` would, even though the bytes embedded below are
+            // the already-decoded RGBA buffer rather than the raw file.
+            const _: &[u8] = ::std::include_bytes!(#include_path);
+
+            static RGBA: &[u8] = &[#(#bytes),*];
+
+            // `Icon::from_rgba` only rejects a buffer whose length doesn't match
+            // `width * height * 4`, which can't happen here since `width`/`height`
+            // and `bytes` were all read off the same decoded `RgbaImage` above.
+            ::druid_shell::Icon::from_rgba(RGBA.to_vec(), #width, #height)
+                .expect("include_icon!: decoded RGBA buffer should always be valid")
+        }
+    };
+
+    expanded.into()
+}
@@ -14,26 +14,43 @@ use core_foundation::base::TCFType;
 use core_foundation::bundle::{CFBundleGetBundleWithIdentifier, CFBundleGetFunctionPointerForName};
 use core_foundation::string::CFString;
 use objc::rc::WeakPtr;
-use objc::{msg_send, sel, sel_impl};
+use objc::{class, msg_send, sel, sel_impl};
 use NSOpenGLPFAOpenGLProfiles::{
     NSOpenGLProfileVersion3_2Core, NSOpenGLProfileVersion4_1Core, NSOpenGLProfileVersionLegacy,
 };
 
 use crate::gl::{
-    GlAttributes, GlProfile, GlRequest, PixelFormat, PixelFormatRequirements, ReleaseBehavior,
-    Robustness,
+    ColorSpace, ContextError, GlAttributes, GlProfile, GlRequest, PixelFormat,
+    PixelFormatRequirements, ReleaseBehavior, Robustness,
 };
 use crate::Error;
 
 #[derive(Clone)]
 pub(crate) struct Context {
     pub(crate) context: WeakPtr,
+    pixel_format: PixelFormat,
 }
 
 impl Default for Context {
     fn default() -> Self {
         Self {
             context: unsafe { WeakPtr::new(nil) },
+            // Never queried before `build()` replaces this placeholder with a
+            // real `Context` holding the format AppKit actually granted.
+            pixel_format: PixelFormat {
+                hardware_accelerated: false,
+                color_bits: 0,
+                alpha_bits: 0,
+                depth_bits: 0,
+                stencil_bits: 0,
+                stereoscopy: false,
+                double_buffer: false,
+                multisampling: None,
+                srgb: false,
+                float_color_buffer: false,
+                color_space: ColorSpace::Srgb,
+                release_behavior: ReleaseBehavior::Flush,
+            },
         }
     }
 }
@@ -49,12 +66,21 @@ impl Context {
         };
         symbol as *const _
     }
+
+    /// Returns the `PixelFormat` AppKit actually granted when this context was
+    /// created, which may differ from what was requested (e.g. a narrower
+    /// `multisampling` level, or `srgb: false` when a wide-gamut/extended
+    /// colorspace was applied instead of sRGB) — callers should check this rather than assume their
+    /// `PixelFormatRequirements` were met exactly.
+    pub(crate) fn get_pixel_format(&self) -> PixelFormat {
+        self.pixel_format.clone()
+    }
 }
 
 pub(crate) fn create_gl_context(
     view: id,
     pf_reqs: &PixelFormatRequirements,
-    gl_attr: &GlAttributes,
+    gl_attr: &GlAttributes<Context>,
 ) -> Result<Context, Error> {
     match gl_attr.robustness {
         Robustness::RobustNoResetNotification | Robustness::RobustLoseContextOnReset => {
@@ -74,12 +100,38 @@ pub(crate) fn create_gl_context(
             );
         }
 
-        let gl_context =
-            NSOpenGLContext::alloc(nil).initWithFormat_shareContext_(pixel_format as id, nil);
+        // `initWithFormat:shareContext:` returns `nil` if the shared context's
+        // pixel format is incompatible, which is also the only compatibility
+        // signal AppKit gives us — there's no separate up-front check to run.
+        let shared_context = gl_attr
+            .sharing
+            .as_ref()
+            .map(|ctx| *ctx.context.load())
+            .unwrap_or(nil);
+        let gl_context = NSOpenGLContext::alloc(nil)
+            .initWithFormat_shareContext_(pixel_format as id, shared_context);
         if gl_context == nil {
+            if shared_context != nil {
+                return Err(anyhow!(ContextError::SharingIncompatible).into());
+            }
             return Err(anyhow!("could not open gl context").into());
         }
 
+        // `NSOpenGLPixelFormat` itself has no attribute for colorspace or
+        // sRGB encoding, so requesting anything beyond the default goes
+        // through the view's `NSWindow` instead, via the same `NSColorSpace`
+        // API AppKit uses for its own wide-gamut/HDR (EDR) rendering.
+        // `hdr` asks for an extended-range (EDR) drawable; if the caller didn't
+        // already request a wide/extended colorspace for that, `ScrgbLinear` is
+        // the one `apply_color_space` actually backs with an extended-range
+        // `NSColorSpace`, so upgrade to it rather than silently granting sRGB.
+        let requested_color_space = if pf_reqs.hdr && pf_reqs.color_space == ColorSpace::Srgb {
+            ColorSpace::ScrgbLinear
+        } else {
+            pf_reqs.color_space
+        };
+        let granted_color_space = apply_color_space(view, requested_color_space);
+
         let pixel_format = {
             let get_attr = |attrib: appkit::NSOpenGLPixelFormatAttribute| -> i32 {
                 let mut value = 0;
@@ -106,12 +158,22 @@ pub(crate) fn create_gl_context(
                 } else {
                     None
                 },
-                srgb: true,
+                srgb: granted_color_space == ColorSpace::Srgb,
+                float_color_buffer: get_attr(appkit::NSOpenGLPFAColorFloat) != 0,
+                color_space: granted_color_space,
+                // `build_nsattributes` rejects any request other than
+                // `ReleaseBehavior::Flush` up front, so this is always what
+                // was actually granted.
+                release_behavior: ReleaseBehavior::Flush,
             }
         };
 
         gl_context.setView_(view);
-        let value = if gl_attr.vsync { 1 } else { 0 };
+        let value = if gl_attr.swap_interval != crate::gl::SwapInterval::DontWait {
+            1
+        } else {
+            0
+        };
         gl_context.setValues_forParameter_(
             &value,
             appkit::NSOpenGLContextParameter::NSOpenGLCPSwapInterval,
@@ -124,13 +186,141 @@ pub(crate) fn create_gl_context(
 
         let context = Context {
             context: WeakPtr::new(gl_context),
+            pixel_format,
         };
         Ok(context)
     }
 }
 
+/// Creates a GL context for offscreen rendering (CI image-diff tests, thumbnail
+/// generation, GPU compute): builds the same pixel format and `NSOpenGLContext`
+/// pair as [`create_gl_context`], but never calls `setView_`, so the context has
+/// no backing drawable. Callers `make_current` it, allocate their own FBO/texture,
+/// and read pixels back.
+///
+/// `double_buffer` and `hardware_accelerated` from `pf_reqs` are overridden to
+/// "don't care": double-buffering only matters for a context that's actually
+/// presented on screen, and allowing software renderers to qualify means this
+/// also works in environments (CI runners, headless servers) with no GPU.
+///
+/// `size` isn't used to size anything on the `NSOpenGLContext` itself — with a
+/// core-profile context, render targets are app-owned FBOs/renderbuffers sized
+/// however the caller likes, rather than anything the context tracks — but debug
+/// builds assert that it's non-zero, since a 0x0 target is never useful.
+///
+/// `gl_attr.sharing` is honored the same way as in [`create_gl_context`], so a
+/// worker thread can build a headless context that shares textures/buffers with
+/// a window's onscreen context (or with another headless context).
+pub(crate) fn create_headless_context(
+    size: (u32, u32),
+    pf_reqs: &PixelFormatRequirements,
+    gl_attr: &GlAttributes<Context>,
+) -> Result<Context, Error> {
+    debug_assert!(
+        size.0 > 0 && size.1 > 0,
+        "headless GL context size must be non-zero"
+    );
+
+    match gl_attr.robustness {
+        Robustness::RobustNoResetNotification | Robustness::RobustLoseContextOnReset => {
+            return Err(anyhow!("You requested robustness, but it is not supported.").into());
+        }
+        _ => (),
+    }
+
+    let pf_reqs = PixelFormatRequirements {
+        hardware_accelerated: None,
+        double_buffer: None,
+        color_bits: pf_reqs.color_bits,
+        float_color_buffer: pf_reqs.float_color_buffer,
+        alpha_bits: pf_reqs.alpha_bits,
+        depth_bits: pf_reqs.depth_bits,
+        stencil_bits: pf_reqs.stencil_bits,
+        multisampling: pf_reqs.multisampling,
+        stereoscopy: pf_reqs.stereoscopy,
+        srgb: pf_reqs.srgb,
+        hdr: pf_reqs.hdr,
+        color_space: pf_reqs.color_space,
+        release_behavior: pf_reqs.release_behavior,
+        x11_visual_xid: pf_reqs.x11_visual_xid,
+    };
+
+    let gl_profile = get_gl_profile(gl_attr, &pf_reqs)?;
+    let attributes = build_nsattributes(&pf_reqs, gl_profile)?;
+
+    unsafe {
+        let pixel_format = NSOpenGLPixelFormat::alloc(nil).initWithAttributes_(&attributes);
+        if pixel_format == nil {
+            return Err(
+                anyhow!("Couldn't find any pixel format that matches the criteria.").into(),
+            );
+        }
+
+        let shared_context = gl_attr
+            .sharing
+            .as_ref()
+            .map(|ctx| *ctx.context.load())
+            .unwrap_or(nil);
+        let gl_context = NSOpenGLContext::alloc(nil)
+            .initWithFormat_shareContext_(pixel_format as id, shared_context);
+        if gl_context == nil {
+            if shared_context != nil {
+                return Err(anyhow!(ContextError::SharingIncompatible).into());
+            }
+            return Err(anyhow!("could not open gl context").into());
+        }
+
+        CGLEnable(
+            gl_context.CGLContextObj() as *mut _,
+            kCGLCECrashOnRemovedFunctions,
+        );
+
+        let granted_pixel_format = {
+            let get_attr = |attrib: appkit::NSOpenGLPixelFormatAttribute| -> i32 {
+                let mut value = 0;
+                NSOpenGLPixelFormat::getValues_forAttribute_forVirtualScreen_(
+                    pixel_format,
+                    &mut value,
+                    attrib,
+                    NSOpenGLContext::currentVirtualScreen(gl_context),
+                );
+                value
+            };
+
+            PixelFormat {
+                hardware_accelerated: get_attr(appkit::NSOpenGLPFAAccelerated) != 0,
+                color_bits: (get_attr(appkit::NSOpenGLPFAColorSize)
+                    - get_attr(appkit::NSOpenGLPFAAlphaSize)) as u8,
+                alpha_bits: get_attr(appkit::NSOpenGLPFAAlphaSize) as u8,
+                depth_bits: get_attr(appkit::NSOpenGLPFADepthSize) as u8,
+                stencil_bits: get_attr(appkit::NSOpenGLPFAStencilSize) as u8,
+                stereoscopy: get_attr(appkit::NSOpenGLPFAStereo) != 0,
+                double_buffer: get_attr(appkit::NSOpenGLPFADoubleBuffer) != 0,
+                multisampling: if get_attr(appkit::NSOpenGLPFAMultisample) > 0 {
+                    Some(get_attr(appkit::NSOpenGLPFASamples) as u16)
+                } else {
+                    None
+                },
+                // There's no `NSWindow` backing a headless context to carry
+                // an `NSColorSpace`, so unlike `create_gl_context` there's
+                // nothing to apply a requested `color_space` to; report the
+                // plain sRGB colorspace this always actually renders in.
+                srgb: true,
+                float_color_buffer: get_attr(appkit::NSOpenGLPFAColorFloat) != 0,
+                color_space: ColorSpace::Srgb,
+                release_behavior: ReleaseBehavior::Flush,
+            }
+        };
+
+        Ok(Context {
+            context: WeakPtr::new(gl_context),
+            pixel_format: granted_pixel_format,
+        })
+    }
+}
+
 fn get_gl_profile(
-    opengl: &GlAttributes,
+    opengl: &GlAttributes<Context>,
     pf_reqs: &PixelFormatRequirements,
 ) -> Result<NSOpenGLPFAOpenGLProfiles, Error> {
     let version = opengl.version.to_gl_version();
@@ -180,7 +370,34 @@ fn get_gl_profile(
         attributes[current_idx] = NSOpenGLPFAOpenGLProfile as u32;
         current_idx += 1;
 
+        // The AppKit version tells us which core profiles the running OS is known
+        // to support, so we can try the best one directly instead of relying
+        // solely on the allocation probe below to discover it.
+        let appkit_version = unsafe { appkit::NSAppKitVersionNumber };
+        let preferred = if appkit_version >= appkit::NSAppKitVersionNumber10_9 {
+            Some(NSOpenGLProfileVersion4_1Core)
+        } else if appkit_version >= appkit::NSAppKitVersionNumber10_7 {
+            Some(NSOpenGLProfileVersion3_2Core)
+        } else {
+            None
+        };
+
+        if let Some(profile) = preferred {
+            attributes[current_idx] = profile as u32;
+            let id = unsafe { NSOpenGLPixelFormat::alloc(nil).initWithAttributes_(&attributes) };
+            if id != nil {
+                unsafe { msg_send![id, release] }
+                return Ok(profile);
+            }
+        }
+        let preferred = preferred.map(|profile| profile as u32);
+
+        // The preferred profile wasn't available (or the OS predates both core
+        // profiles); fall back to probing every profile we know about.
         for &profile in &[NSOpenGLProfileVersion4_1Core, NSOpenGLProfileVersion3_2Core] {
+            if preferred == Some(profile as u32) {
+                continue;
+            }
             attributes[current_idx] = profile as u32;
             let id = unsafe { NSOpenGLPixelFormat::alloc(nil).initWithAttributes_(&attributes) };
             if id != nil {
@@ -197,6 +414,48 @@ fn get_gl_profile(
     }
 }
 
+/// Requests `color_space` on the `NSWindow` backing `view`, using the same
+/// `NSColorSpace` API AppKit itself uses for wide-gamut/HDR (EDR) rendering,
+/// and returns the colorspace that was actually granted.
+///
+/// `NSOpenGLPixelFormat` has no colorspace attribute of its own — the
+/// pixel-format attribute list only controls bit depths — so this is the
+/// real mechanism available for `PixelFormatRequirements::color_space` on
+/// mac. There's no `NSColorSpace` constructor for scRGB-linear or BT.2100 PQ;
+/// the closest documented analog for either is the extended-range sRGB space
+/// used for EDR content, so both fall back to that and report
+/// `ColorSpace::ScrgbLinear` rather than claiming the exact space requested.
+fn apply_color_space(view: id, requested: ColorSpace) -> ColorSpace {
+    unsafe {
+        let (color_space, granted): (id, ColorSpace) = match requested {
+            ColorSpace::Srgb => (
+                msg_send![class!(NSColorSpace), sRGBColorSpace],
+                ColorSpace::Srgb,
+            ),
+            ColorSpace::LinearDisplayP3 => (
+                msg_send![class!(NSColorSpace), displayP3ColorSpace],
+                ColorSpace::LinearDisplayP3,
+            ),
+            ColorSpace::ScrgbLinear | ColorSpace::Bt2100Pq => (
+                msg_send![class!(NSColorSpace), extendedSRGBColorSpace],
+                ColorSpace::ScrgbLinear,
+            ),
+        };
+
+        if color_space == nil {
+            return ColorSpace::Srgb;
+        }
+
+        let window: id = msg_send![view, window];
+        if window == nil {
+            return ColorSpace::Srgb;
+        }
+
+        let _: () = msg_send![window, setColorSpace: color_space];
+        granted
+    }
+}
+
 fn build_nsattributes(
     pf_reqs: &PixelFormatRequirements,
     profile: NSOpenGLPFAOpenGLProfiles,
@@ -242,7 +501,7 @@ fn build_nsattributes(
     }
 
     if pf_reqs.stereoscopy {
-        unimplemented!(); // TODO:
+        attributes.push(appkit::NSOpenGLPFAStereo as u32);
     }
 
     if pf_reqs.float_color_buffer {
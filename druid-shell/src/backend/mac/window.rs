@@ -18,6 +18,7 @@
 
 use std::ffi::c_void;
 use std::mem;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex, Weak};
 use std::time::Instant;
 
@@ -33,7 +34,7 @@ use cocoa::foundation::{
 };
 use lazy_static::lazy_static;
 use objc::declare::ClassDecl;
-use objc::rc::WeakPtr;
+use objc::rc::{StrongPtr, WeakPtr};
 use objc::runtime::{Class, Object, Protocol, Sel};
 use objc::{class, msg_send, sel, sel_impl};
 use piet_wgpu::WgpuRenderer;
@@ -98,12 +99,191 @@ mod levels {
     }
 }
 
+/// Minimal CoreVideo FFI for driving `request_anim_frame` off the display's vblank
+/// instead of `performSelectorOnMainThread:` alone, which runs as fast as the main
+/// thread gets to it rather than synchronized to the screen's refresh rate.
+mod cv_display_link {
+    use std::ffi::c_void;
+
+    pub type CVDisplayLinkRef = *mut c_void;
+    pub type CVReturn = i32;
+    pub type CVOptionFlags = u64;
+
+    /// Only the fields `CVDisplayLinkGetNominalOutputVideoRefreshPeriod` returns that
+    /// this backend actually reads; the full `CVSMPTETime`-adjacent layout doesn't
+    /// matter here.
+    #[repr(C)]
+    pub struct CVTime {
+        pub time_value: i64,
+        pub time_scale: i32,
+        pub time_flags: i32,
+    }
+
+    pub type CVDisplayLinkOutputCallback = extern "C" fn(
+        display_link: CVDisplayLinkRef,
+        in_now: *const c_void,
+        in_output_time: *const c_void,
+        flags_in: CVOptionFlags,
+        flags_out: *mut CVOptionFlags,
+        display_link_context: *mut c_void,
+    ) -> CVReturn;
+
+    #[link(name = "CoreVideo", kind = "framework")]
+    extern "C" {
+        pub fn CVDisplayLinkCreateWithCGDisplay(
+            display_id: u32,
+            display_link_out: *mut CVDisplayLinkRef,
+        ) -> CVReturn;
+        pub fn CVDisplayLinkSetCurrentCGDisplay(
+            display_link: CVDisplayLinkRef,
+            display_id: u32,
+        ) -> CVReturn;
+        pub fn CVDisplayLinkSetOutputCallback(
+            display_link: CVDisplayLinkRef,
+            callback: CVDisplayLinkOutputCallback,
+            user_info: *mut c_void,
+        ) -> CVReturn;
+        pub fn CVDisplayLinkStart(display_link: CVDisplayLinkRef) -> CVReturn;
+        pub fn CVDisplayLinkStop(display_link: CVDisplayLinkRef) -> CVReturn;
+        pub fn CVDisplayLinkRelease(display_link: CVDisplayLinkRef);
+        pub fn CVDisplayLinkIsRunning(display_link: CVDisplayLinkRef) -> u8;
+        pub fn CVDisplayLinkGetNominalOutputVideoRefreshPeriod(
+            display_link: CVDisplayLinkRef,
+        ) -> CVTime;
+    }
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        pub fn CGMainDisplayID() -> u32;
+    }
+}
+
+use cv_display_link::{CVDisplayLinkRef, CVReturn, CVOptionFlags};
+
+/// Raw `libdispatch` bindings used to marshal AppKit mutator calls onto the main
+/// thread from [`run_on_main_thread`].
+mod dispatch_queue {
+    use std::ffi::c_void;
+
+    #[repr(C)]
+    pub struct dispatch_queue_s {
+        _private: [u8; 0],
+    }
+    pub type dispatch_queue_t = *mut dispatch_queue_s;
+    pub type dispatch_function_t = extern "C" fn(*mut c_void);
+
+    extern "C" {
+        pub fn dispatch_get_main_queue() -> dispatch_queue_t;
+        pub fn dispatch_async_f(
+            queue: dispatch_queue_t,
+            context: *mut c_void,
+            work: dispatch_function_t,
+        );
+    }
+}
+
+fn is_main_thread() -> bool {
+    unsafe {
+        let is_main: BOOL = msg_send![class!(NSThread), isMainThread];
+        is_main == YES
+    }
+}
+
+/// Runs `f` on the main thread: inline if already there, otherwise marshaled onto
+/// the main dispatch queue via `dispatch_async_f`. AppKit calls made from a
+/// background thread are undefined behavior, so every window mutator that might be
+/// called off the main thread (`set_title`, `resizable`, `set_window_state`,
+/// `set_level`, `set_menu`) goes through this instead of calling `msg_send!` inline.
+fn run_on_main_thread(f: impl FnOnce() + 'static) {
+    if is_main_thread() {
+        f();
+        return;
+    }
+
+    // The boxed closure may capture AppKit object pointers (`id`), which aren't
+    // `Send`, but it's handed off to the main thread via `dispatch_async_f` and
+    // never touched from this thread again, so asserting `Send` here is sound.
+    struct MainThreadClosure(Box<dyn FnOnce()>);
+    unsafe impl Send for MainThreadClosure {}
+
+    extern "C" fn trampoline(context: *mut c_void) {
+        let closure = unsafe { Box::from_raw(context as *mut MainThreadClosure) };
+        (closure.0)();
+    }
+
+    let context = Box::into_raw(Box::new(MainThreadClosure(Box::new(f)))) as *mut c_void;
+    unsafe {
+        dispatch_queue::dispatch_async_f(
+            dispatch_queue::dispatch_get_main_queue(),
+            context,
+            trampoline,
+        );
+    }
+}
+
+/// macOS has no per-window titlebar icon; the closest analog `set_window_icon`
+/// has to act on is the shared application (Dock) icon, which AppKit draws at
+/// up to this size.
+const DOCK_ICON_SIZE: u32 = 512;
+
 #[derive(Clone, PartialEq)]
-pub(crate) struct PlatformIcon {}
+pub(crate) struct PlatformIcon {
+    /// Every `(rgba, width, height)` frame the icon was built from; `best_fit`
+    /// picks whichever is closest when building the `NSImage` AppKit wants.
+    frames: Vec<(Vec<u8>, u32, u32)>,
+}
 
 impl PlatformIcon {
-    pub fn from_rgba(_rgba: Vec<u8>, _width: u32, _height: u32) -> Result<Self, Error> {
-        Err(Error::Other(anyhow::anyhow!("icon not supported").into()))
+    pub fn from_rgba(rgba: Vec<u8>, width: u32, height: u32) -> Result<Self, std::io::Error> {
+        Self::from_rgba_sizes(vec![(rgba, width, height)])
+    }
+
+    pub fn from_rgba_sizes(frames: Vec<(Vec<u8>, u32, u32)>) -> Result<Self, std::io::Error> {
+        Ok(PlatformIcon { frames })
+    }
+
+    /// Returns the stored frame whose `(width, height)` is closest to `size`.
+    fn best_fit(&self, size: u32) -> Option<&(Vec<u8>, u32, u32)> {
+        self.frames.iter().min_by_key(|(_, width, height)| {
+            (*width as i64 - size as i64).abs() + (*height as i64 - size as i64).abs()
+        })
+    }
+
+    /// Builds an `NSImage` from the frame closest to `size`, via an
+    /// `NSBitmapImageRep` that owns its own pixel storage (`planes: NULL`,
+    /// same as [`WindowHandle::make_cursor`]'s) which is then filled in directly.
+    unsafe fn to_nsimage(&self, size: u32) -> Option<id> {
+        let (rgba, width, height) = self.best_fit(size)?;
+        let (width, height) = (*width as NSInteger, *height as NSInteger);
+
+        let bitmap: id = msg_send![class!(NSBitmapImageRep), alloc];
+        let bitmap: id = msg_send![bitmap,
+            initWithBitmapDataPlanes: std::ptr::null_mut::<*mut u8>()
+            pixelsWide: width
+            pixelsHigh: height
+            bitsPerSample: 8_i64
+            samplesPerPixel: 4_i64
+            hasAlpha: YES
+            isPlanar: NO
+            colorSpaceName: make_nsstring("NSDeviceRGBColorSpace")
+            bitmapFormat: 0_u64
+            bytesPerRow: (width * 4)
+            bitsPerPixel: 32_i64
+        ];
+        if bitmap == nil {
+            tracing::warn!("Failed to allocate NSBitmapImageRep for window icon");
+            return None;
+        }
+
+        let dest: *mut u8 = msg_send![bitmap, bitmapData];
+        std::ptr::copy_nonoverlapping(rgba.as_ptr(), dest, rgba.len());
+
+        let size = NSSize::new(width as f64, height as f64);
+        let image: id = msg_send![class!(NSImage), alloc];
+        let image: id = msg_send![image, initWithSize: size];
+        let () = msg_send![image, addRepresentation: bitmap];
+        let () = msg_send![bitmap, release];
+        Some(image)
     }
 }
 
@@ -158,7 +338,11 @@ pub(crate) struct WindowBuilder {
     show_titlebar: bool,
     transparent: bool,
     pf_reqs: Option<PixelFormatRequirements>,
-    gl_attr: Option<GlAttributes>,
+    gl_attr: Option<GlAttributes<Context>>,
+    mouse_coalescing: bool,
+    titlebar_button_inset: Option<Vec2>,
+    #[cfg(feature = "raw-win-handle")]
+    parent: Option<RawWindowHandle>,
 }
 
 #[derive(Clone)]
@@ -197,12 +381,121 @@ struct ViewState {
     parent: Option<crate::WindowHandle>,
     context_menu_pos: Point,
     dragable_area: Region,
+    /// Set via [`WindowHandle::handle_titlebar`]: when true, the entire content view
+    /// is draggable, not just `dragable_area`.
+    handle_titlebar: bool,
     drag_window: bool,
+    /// The most recent tablet/pressure sample seen on a mouse event, if any.
+    ///
+    /// There's no `MouseEvent`/`WinHandler` extension point in this tree to deliver
+    /// this data through yet (that lives in `crate::mouse`/`crate::window`); it's
+    /// tracked here for a future pointer-event pipeline to pick up.
+    last_tablet: Option<TabletData>,
+    /// Set once teardown has run, so late-arriving AppKit callbacks (a timer or
+    /// `runIdle` that was already scheduled before the window closed) become no-ops
+    /// instead of reaching into state that's about to be dropped.
+    closed: bool,
+    /// Every outstanding `NSTimer` requested via [`WindowHandle::request_timer`],
+    /// invalidated during teardown so none can fire after close.
+    timers: Vec<WeakPtr>,
+    tracking_area: WeakPtr,
+    /// The CVDisplayLink backing `request_anim_frame`, created lazily on first use
+    /// and retargeted to the window's current screen as it moves. Null until then.
+    display_link: CVDisplayLinkRef,
+    /// Owns the context the display link's callback is invoked with; see
+    /// [`DisplayLinkContext`].
+    display_link_context: Option<Box<DisplayLinkContext>>,
+    /// Set by `request_anim_frame`, cleared by the display link's vblank callback
+    /// (on the CoreVideo thread) just before it asks the main thread to redraw. The link
+    /// stops itself once a tick finds this already clear (no frame was requested
+    /// since the last one it delivered).
+    anim_pending: Arc<AtomicBool>,
+    /// The nominal refresh period last reported by `display_link`, in seconds.
+    ///
+    /// There's no `WinHandler::paint` extension point in this tree to deliver an
+    /// accurate frame timestamp/delta through yet (that trait lives in
+    /// `crate::window`, outside this snapshot), so it's tracked here for a future
+    /// animation-timing pipeline to pick up.
+    nominal_refresh_period: Option<f64>,
+    /// The view's light/dark appearance, refreshed whenever AppKit calls
+    /// `viewDidChangeEffectiveAppearance`.
+    ///
+    /// There's no `WinHandler::appearance_changed` extension point in this tree to
+    /// deliver the new value to yet (that trait lives in `crate::window`, outside
+    /// this snapshot), so for now this is only tracked here. Exposed directly via
+    /// [`WindowHandle::current_appearance`], so a window can at least read its
+    /// current appearance on demand instead of having no access to it at all.
+    appearance: Appearance,
+    /// The `backingScaleFactor` last delivered to `handler.scale()`, so
+    /// `window_did_change_backing_properties` can tell whether the scale actually
+    /// changed (as opposed to some other backing property, e.g. color space).
+    last_scale: f64,
+    /// Whether the view's `NSTextInputClient` methods should currently accept IME
+    /// composition, set via [`WindowHandle::set_ime_allowed`].
+    ///
+    /// `hasMarkedText`/`setMarkedText:selectedRange:replacementRange:` (where this
+    /// would actually gate composition, and where preedit/commit would be split into
+    /// distinct events) are implemented in `super::text_input`, outside this
+    /// snapshot, so for now this only records the caller's intent.
+    ime_allowed: bool,
 }
 
-#[derive(Clone, PartialEq)]
-// TODO: support custom cursors
-pub struct CustomCursor;
+/// The system's light/dark appearance, as observed from `NSAppearance`.
+///
+/// See [`ViewState::appearance`] for how (and how far) this is currently tracked.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Appearance {
+    Light,
+    Dark,
+}
+
+/// Reads whether `view`'s `effectiveAppearance` best-matches
+/// `NSAppearanceNameDarkAqua` or `NSAppearanceNameAqua`.
+fn current_appearance(view: id) -> Appearance {
+    unsafe {
+        let appearance: id = msg_send![view, effectiveAppearance];
+        let dark_aqua = make_nsstring("NSAppearanceNameDarkAqua");
+        let aqua = make_nsstring("NSAppearanceNameAqua");
+        let names = NSArray::arrayWithObjects(nil, &[dark_aqua, aqua]);
+        let best_match: id = msg_send![appearance, bestMatchFromAppearancesWithNames: names];
+        if best_match != nil {
+            let is_dark: BOOL = msg_send![best_match, isEqualToString: dark_aqua];
+            if is_dark == YES {
+                return Appearance::Dark;
+            }
+        }
+        Appearance::Light
+    }
+}
+
+extern "C" fn view_did_change_effective_appearance(this: &mut Object, _: Sel) {
+    unsafe {
+        let view_state: *mut c_void = *this.get_ivar("viewState");
+        let view_state = &mut *(view_state as *mut ViewState);
+        if view_state.closed {
+            return;
+        }
+        // Not delivered to `WinHandler` (see `ViewState::appearance`), just tracked
+        // so `WindowHandle::current_appearance` stays current.
+        view_state.appearance = current_appearance(this as *mut Object as id);
+    }
+}
+
+/// A cursor image created from a [`CursorDesc`], retained for as long as it
+/// might still be set via `set_cursor`.
+///
+/// Holds a `StrongPtr` rather than a `WeakPtr`: nothing in AppKit tracks a
+/// custom `NSCursor` once it's created, so a weak reference to it would let
+/// the `+1` from `make_cursor`'s `alloc`/`initWithImage:hotSpot:` leak
+/// forever instead of being released when this is dropped.
+#[derive(Clone)]
+pub struct CustomCursor(StrongPtr);
+
+impl PartialEq for CustomCursor {
+    fn eq(&self, other: &Self) -> bool {
+        *self.0 == *other.0
+    }
+}
 
 impl WindowBuilder {
     pub fn new(_app: Application) -> WindowBuilder {
@@ -220,6 +513,10 @@ impl WindowBuilder {
             transparent: false,
             pf_reqs: None,
             gl_attr: None,
+            mouse_coalescing: true,
+            titlebar_button_inset: None,
+            #[cfg(feature = "raw-win-handle")]
+            parent: None,
         }
     }
 
@@ -227,6 +524,31 @@ impl WindowBuilder {
         self.handler = Some(handler);
     }
 
+    /// Sets whether AppKit may coalesce consecutive `mouseMoved:`/`mouseDragged:`
+    /// events before delivering them.
+    ///
+    /// Coalescing is on by default, matching AppKit's own default: most apps don't
+    /// need every intermediate sample. Drawing and signal-editing UIs that need the
+    /// full-frequency stream (so a fast stroke doesn't lose points) should pass
+    /// `false`.
+    pub fn set_mouse_coalescing(&mut self, enabled: bool) {
+        self.mouse_coalescing = enabled;
+    }
+
+    /// Embeds the window in a host-provided parent view instead of creating its own
+    /// `NSWindow`.
+    ///
+    /// This is the path a plugin editor (AU, VST) uses: the host owns the top-level
+    /// window, and only hands druid an `NSView` to draw into. When a parent is set,
+    /// [`WindowBuilder::build`] adds the `DruidView` as a subview of `parent` and
+    /// skips `NSWindow` creation entirely; window-level operations on the resulting
+    /// [`WindowHandle`] (title, level, ...) become no-ops, while `close` tears the
+    /// view down and removes it from `parent`. Requires the `raw-win-handle` feature.
+    #[cfg(feature = "raw-win-handle")]
+    pub fn set_parent(&mut self, parent: RawWindowHandle) {
+        self.parent = Some(parent);
+    }
+
     pub fn set_size(&mut self, size: Size) {
         self.size = size;
     }
@@ -235,7 +557,15 @@ impl WindowBuilder {
         self.min_size = Some(size);
     }
 
-    pub fn set_window_icon(&mut self, _window_icon: Icon) {}
+    // macOS has no per-window titlebar icon, so the only meaningful thing this
+    // can do is set the shared application (Dock) icon to it.
+    pub fn set_window_icon(&mut self, window_icon: Icon) {
+        unsafe {
+            if let Some(image) = window_icon.inner.to_nsimage(DOCK_ICON_SIZE) {
+                NSApp().setApplicationIconImage_(image);
+            }
+        }
+    }
 
     pub fn resizable(&mut self, resizable: bool) {
         self.resizable = resizable;
@@ -245,6 +575,16 @@ impl WindowBuilder {
         self.show_titlebar = show_titlebar;
     }
 
+    /// Repositions the standard close/minimize/zoom buttons by `inset` (from the
+    /// window's top-left corner) once the window is built.
+    ///
+    /// Meant for use alongside `show_titlebar(false)`: with the native titlebar
+    /// hidden, the traffic-light buttons otherwise stay at their default position
+    /// and can overlap custom-drawn content.
+    pub fn set_titlebar_button_inset(&mut self, inset: Vec2) {
+        self.titlebar_button_inset = Some(inset);
+    }
+
     pub fn set_transparent(&mut self, transparent: bool) {
         self.transparent = transparent;
     }
@@ -271,6 +611,11 @@ impl WindowBuilder {
 
     pub fn build(self) -> Result<WindowHandle, Error> {
         assert_main_thread();
+        set_mouse_coalescing_enabled(self.mouse_coalescing);
+        #[cfg(feature = "raw-win-handle")]
+        if let Some(parent) = self.parent {
+            return self.build_embedded(parent);
+        }
         unsafe {
             let mut style_mask = NSWindowStyleMask::NSClosableWindowMask
                 | NSWindowStyleMask::NSMiniaturizableWindowMask;
@@ -373,6 +718,15 @@ impl WindowBuilder {
                     _ => {}
                 }
                 handle.set_level(level);
+
+                #[cfg(feature = "raw-win-handle")]
+                if let Some(parent) = &(*view_state).parent {
+                    attach_child_window(window, parent);
+                }
+            }
+
+            if let Some(inset) = self.titlebar_button_inset {
+                handle.set_titlebar_button_inset(inset);
             }
 
             let scale = NSScreen::backingScaleFactor(window) as f64;
@@ -381,6 +735,83 @@ impl WindowBuilder {
             let frame = NSView::frame(content_view);
             (*view_state).handler.connect(&handle.clone().into());
             (*view_state).handler.scale(Scale::new(scale, scale));
+            (*view_state).last_scale = scale;
+            (*view_state)
+                .handler
+                .size(Size::new(frame.size.width, frame.size.height));
+
+            let renderer = &mut (*view_state).renderer;
+            renderer.set_size(Size::new(
+                frame.size.width * scale,
+                frame.size.height * scale,
+            ));
+            renderer.set_scale(scale);
+
+            Ok(handle)
+        }
+    }
+
+    /// The embedded-mode counterpart to [`WindowBuilder::build`], used when
+    /// [`WindowBuilder::set_parent`] was called: adds the `DruidView` as a subview of
+    /// the host's view and never allocates an `NSWindow`.
+    #[cfg(feature = "raw-win-handle")]
+    fn build_embedded(self, parent: RawWindowHandle) -> Result<WindowHandle, Error> {
+        assert_main_thread();
+        unsafe {
+            let parent_view = match parent {
+                RawWindowHandle::MacOS(handle) => handle.ns_view as id,
+                _ => {
+                    return Err(Error::Other(
+                        anyhow::anyhow!("set_parent requires a macOS raw window handle").into(),
+                    ))
+                }
+            };
+            if parent_view == nil {
+                return Err(Error::Other(
+                    anyhow::anyhow!("parent view passed to set_parent is nil").into(),
+                ));
+            }
+
+            let gl_context = create_gl_context(
+                parent_view,
+                &self.pf_reqs.unwrap_or_default(),
+                &self.gl_attr.unwrap_or_default(),
+            )?;
+
+            let _: () = msg_send![*gl_context.context.load(), update];
+            gl_context.context.load().makeCurrentContext();
+            let renderer = WgpuRenderer::new(|s| gl_context.get_proc_address(s) as *const _)
+                .map_err(|_| {
+                    Error::Other(anyhow::anyhow!("create opengl backend failed").into())
+                })?;
+
+            // No host `NSWindow` exists yet, so the view is parented directly and its
+            // `ViewState::nswindow` stays nil; window-level operations on the resulting
+            // handle become no-ops because AppKit tolerates messaging a nil window.
+            let view = make_view(nil, self.handler.expect("view"), gl_context.clone(), renderer);
+            let frame = NSView::frame(parent_view);
+            view.initWithFrame_(frame);
+
+            parent_view.addSubview_(view);
+
+            let view_state: *mut c_void = *(*view).get_ivar("viewState");
+            let view_state = &mut *(view_state as *mut ViewState);
+            let handle = WindowHandle {
+                nsview: WeakPtr::new(view),
+                gl_context,
+                idle_queue: Arc::downgrade(&view_state.idle_queue),
+            };
+
+            let host_window: id = msg_send![parent_view, window];
+            let scale = if host_window != nil {
+                NSScreen::backingScaleFactor(host_window) as f64
+            } else {
+                1.0
+            };
+
+            (*view_state).handler.connect(&handle.clone().into());
+            (*view_state).handler.scale(Scale::new(scale, scale));
+            (*view_state).last_scale = scale;
             (*view_state)
                 .handler
                 .size(Size::new(frame.size.width, frame.size.height));
@@ -469,6 +900,10 @@ lazy_static! {
             sel!(windowDidResignKey:),
             window_did_resign_key as extern "C" fn(&mut Object, Sel, id),
         );
+        decl.add_method(
+            sel!(viewDidChangeEffectiveAppearance),
+            view_did_change_effective_appearance as extern "C" fn(&mut Object, Sel),
+        );
         decl.add_method(
             sel!(setFrameSize:),
             set_frame_size as extern "C" fn(&mut Object, Sel, NSSize),
@@ -525,6 +960,10 @@ lazy_static! {
             sel!(magnifyWithEvent:),
             pinch_event as extern "C" fn(&mut Object, Sel, id),
         );
+        decl.add_method(
+            sel!(pressureChangeWithEvent:),
+            pressure_change as extern "C" fn(&mut Object, Sel, id),
+        );
         decl.add_method(
             sel!(keyDown:),
             key_down as extern "C" fn(&mut Object, Sel, id),
@@ -561,6 +1000,10 @@ lazy_static! {
             sel!(windowWillClose:),
             window_will_close as extern "C" fn(&mut Object, Sel, id),
         );
+        decl.add_method(
+            sel!(druidTeardown),
+            druid_teardown as extern "C" fn(&mut Object, Sel),
+        );
         decl.add_method(
             sel!(windowDidMove:),
             window_did_move as extern "C" fn(&mut Object, Sel, id),
@@ -613,6 +1056,13 @@ lazy_static! {
 }
 
 /// Acquires a lock to an `InputHandler`, passes it to a closure, and releases the lock.
+///
+/// This is the seam `super::text_input`'s `NSTextInputClient` method implementations
+/// (`insertText:replacementRange:`, `setMarkedText:selectedRange:replacementRange:`,
+/// `firstRectForCharacterRange:actualRange:`, etc., registered above) use to read and
+/// mutate the active field's document, so composition/marked-text updates and caret-rect
+/// queries for positioning the candidate window all route through the same
+/// `TextFieldToken` input handler as committed text.
 pub(super) fn with_edit_lock_from_window<R>(
     this: &mut Object,
     mutable: bool,
@@ -678,7 +1128,19 @@ fn make_view(
             parent: None,
             context_menu_pos: Point::ZERO,
             dragable_area: Region::EMPTY,
+            handle_titlebar: false,
             drag_window: false,
+            last_tablet: None,
+            closed: false,
+            timers: Vec::new(),
+            tracking_area: WeakPtr::new(tracking_area),
+            display_link: std::ptr::null_mut(),
+            display_link_context: None,
+            anim_pending: Arc::new(AtomicBool::new(false)),
+            nominal_refresh_period: None,
+            appearance: current_appearance(view),
+            last_scale: 1.0,
+            ime_allowed: true,
         };
 
         let state_ptr = Box::into_raw(Box::new(view_state)) as *mut c_void;
@@ -702,10 +1164,42 @@ lazy_static! {
         extern "C" fn canBecomeKeyWindow(_this: &Object, _sel: Sel) -> BOOL {
             YES
         }
+        decl.add_method(
+            sel!(sendEvent:),
+            send_event as extern "C" fn(&mut Object, Sel, id),
+        );
         WindowClass(decl.register())
     };
 }
 
+/// `NSEventType.keyUp`'s raw value.
+const NS_EVENT_TYPE_KEY_UP: NSUInteger = 11;
+/// `NSEventModifierFlags.command`'s raw value.
+const NS_EVENT_MODIFIER_FLAG_COMMAND: NSUInteger = 1 << 20;
+
+/// AppKit's default `-[NSWindow sendEvent:]` drops `keyUp:` for any key pressed
+/// while Command is held, on the assumption it was consumed as a key equivalent, so
+/// `key_up` handlers never see it. Forward that case straight to the first
+/// responder's existing `keyUp:` path instead of calling through to the default
+/// (suppressing) behavior; every other event is passed to `super` unchanged.
+extern "C" fn send_event(this: &mut Object, _sel: Sel, event: id) {
+    unsafe {
+        let event_type: NSUInteger = msg_send![event, type];
+        if event_type == NS_EVENT_TYPE_KEY_UP {
+            let modifier_flags: NSUInteger = msg_send![event, modifierFlags];
+            if modifier_flags & NS_EVENT_MODIFIER_FLAG_COMMAND != 0 {
+                let first_responder: id = msg_send![this as *const _, firstResponder];
+                if first_responder != nil {
+                    let () = msg_send![first_responder, keyUp: event];
+                }
+                return;
+            }
+        }
+        let superclass = msg_send![this, superclass];
+        let () = msg_send![super(this, superclass), sendEvent: event];
+    }
+}
+
 extern "C" fn set_frame_size(this: &mut Object, _: Sel, size: NSSize) {
     unsafe {
         let view_state: *mut c_void = *this.get_ivar("viewState");
@@ -723,6 +1217,55 @@ extern "C" fn set_frame_size(this: &mut Object, _: Sel, size: NSSize) {
     }
 }
 
+/// Toggles AppKit's coalescing of consecutive mouse-move events.
+///
+/// This is process-wide AppKit state (`+[NSEvent setMouseCoalescingEnabled:]`), not
+/// per-window; `cocoa` doesn't declare the selector, so it's sent directly. With
+/// coalescing off, `mouseMoved:`/`mouseDragged:` already fire once per hardware
+/// sample, so [`mouse_move`] needs no extra per-event expansion.
+fn set_mouse_coalescing_enabled(enabled: bool) {
+    let enabled = if enabled { YES } else { NO };
+    unsafe {
+        let () = msg_send![class!(NSEvent), setMouseCoalescingEnabled: enabled];
+    }
+}
+
+/// Pressure/tilt/rotation data AppKit attaches to tablet-originated mouse events.
+///
+/// See [`ViewState::last_tablet`] for why this doesn't yet flow through to
+/// `WinHandler`.
+#[derive(Clone, Copy, Debug, Default)]
+struct TabletData {
+    pressure: f64,
+    tilt: Vec2,
+    rotation: f64,
+    tangential_pressure: f64,
+}
+
+/// `NSEventSubtypeTabletPoint`; not exposed by the `cocoa` crate, so sent raw.
+const NS_EVENT_SUBTYPE_TABLET_POINT: NSInteger = 1;
+
+/// Reads tablet pressure/tilt/rotation off `nsevent`, if it carries any (i.e. its
+/// `subtype` is `NSEventSubtypeTabletPoint`).
+fn tablet_data(nsevent: id) -> Option<TabletData> {
+    unsafe {
+        let subtype: NSInteger = msg_send![nsevent, subtype];
+        if subtype != NS_EVENT_SUBTYPE_TABLET_POINT {
+            return None;
+        }
+        let pressure: f32 = msg_send![nsevent, pressure];
+        let tilt: NSPoint = msg_send![nsevent, tilt];
+        let rotation: f32 = msg_send![nsevent, rotation];
+        let tangential_pressure: f32 = msg_send![nsevent, tangentialPressure];
+        Some(TabletData {
+            pressure: pressure as f64,
+            tilt: Vec2::new(tilt.x, tilt.y),
+            rotation: rotation as f64,
+            tangential_pressure: tangential_pressure as f64,
+        })
+    }
+}
+
 fn mouse_event(
     nsevent: id,
     view: id,
@@ -803,18 +1346,21 @@ fn mouse_down(this: &mut Object, nsevent: id, button: MouseButton) {
         let count = nsevent.clickCount() as u8;
         let focus = view_state.focus_click && button == MouseButton::Left;
         let event = mouse_event(nsevent, this as id, count, focus, button, Vec2::ZERO);
+        view_state.last_tablet = tablet_data(nsevent);
 
         view_state.drag_window = false;
         if count == 1 && button == MouseButton::Left {
-            for rect in view_state.dragable_area.rects() {
-                if rect.contains(event.pos) {
-                    let _: () = msg_send![
-                        *(*view_state).nswindow.load(),
-                        performWindowDragWithEvent: nsevent
-                    ];
-                    view_state.drag_window = true;
-                    break;
-                }
+            let draggable = view_state.handle_titlebar
+                || view_state
+                    .dragable_area
+                    .rects()
+                    .any(|rect| rect.contains(event.pos));
+            if draggable {
+                let _: () = msg_send![
+                    *(*view_state).nswindow.load(),
+                    performWindowDragWithEvent: nsevent
+                ];
+                view_state.drag_window = true;
             }
         }
 
@@ -876,6 +1422,7 @@ extern "C" fn mouse_move(this: &mut Object, _: Sel, nsevent: id) {
         if view_state.drag_window {
             return;
         }
+        view_state.last_tablet = tablet_data(nsevent);
         let event = mouse_event(nsevent, this as id, 0, false, MouseButton::None, Vec2::ZERO);
         (*view_state).handler.mouse_move(&event);
     }
@@ -936,6 +1483,20 @@ extern "C" fn pinch_event(this: &mut Object, _: Sel, nsevent: id) {
     }
 }
 
+/// Force Touch stage/pressure changes on pressure-sensitive trackpads.
+extern "C" fn pressure_change(this: &mut Object, _: Sel, nsevent: id) {
+    unsafe {
+        let view_state: *mut c_void = *this.get_ivar("viewState");
+        let view_state = &mut *(view_state as *mut ViewState);
+
+        let pressure: f32 = msg_send![nsevent, pressure];
+        view_state.last_tablet = Some(TabletData {
+            pressure: pressure as f64,
+            ..view_state.last_tablet.unwrap_or_default()
+        });
+    }
+}
+
 extern "C" fn key_down(this: &mut Object, _: Sel, nsevent: id) {
     let view_state = unsafe {
         let view_state: *mut c_void = *this.get_ivar("viewState");
@@ -986,13 +1547,30 @@ extern "C" fn view_will_draw(this: &mut Object, _: Sel) {
 
 extern "C" fn draw_rect(this: &mut Object, _: Sel, dirtyRect: NSRect) {
     unsafe {
-        // FIXME: use the actual invalid region instead of just this bounding box.
-        // https://developer.apple.com/documentation/appkit/nsview/1483772-getrectsbeingdrawn?language=objc
-        let rect = Rect::from_origin_size(
-            (dirtyRect.origin.x, dirtyRect.origin.y),
-            (dirtyRect.size.width, dirtyRect.size.height),
-        );
-        let invalid = Region::from(rect);
+        // Ask AppKit for the actual list of dirty rectangles rather than just the
+        // bounding box in `dirtyRect`, so scattered small updates (a blinking caret,
+        // a single repainted list row) don't force a full-area repaint.
+        let mut rects_ptr: *const NSRect = std::ptr::null();
+        let mut rects_count: NSInteger = 0;
+        let () = msg_send![this as *const _,
+            getRectsBeingDrawn: &mut rects_ptr
+            count: &mut rects_count
+        ];
+
+        let mut invalid = Region::EMPTY;
+        if !rects_ptr.is_null() && rects_count > 0 {
+            for r in std::slice::from_raw_parts(rects_ptr, rects_count as usize) {
+                invalid.add_rect(Rect::from_origin_size(
+                    (r.origin.x, r.origin.y),
+                    (r.size.width, r.size.height),
+                ));
+            }
+        } else {
+            invalid.add_rect(Rect::from_origin_size(
+                (dirtyRect.origin.x, dirtyRect.origin.y),
+                (dirtyRect.size.width, dirtyRect.size.height),
+            ));
+        }
 
         let view_state: *mut c_void = *this.get_ivar("viewState");
         let view_state = &mut *(view_state as *mut ViewState);
@@ -1059,11 +1637,51 @@ fn set_position_deferred(this: &mut Object, _view_state: &mut ViewState, positio
     }
 }
 
+/// Drives deterministic window teardown: invoked from [`WindowHandle::close`] and
+/// from `windowWillClose:`, whichever happens first. Invalidates every outstanding
+/// timer, drops anything left in the idle queue, and removes the tracking area, so
+/// a timer or idle callback already in flight when the window closes becomes a no-op
+/// in [`run_idle`] / [`handle_timer`] instead of reaching into a `ViewState` that's
+/// about to be freed.
+extern "C" fn druid_teardown(this: &mut Object, _: Sel) {
+    unsafe {
+        let view_state: *mut c_void = *this.get_ivar("viewState");
+        let view_state = &mut *(view_state as *mut ViewState);
+        if view_state.closed {
+            return;
+        }
+        view_state.closed = true;
+
+        for timer in view_state.timers.drain(..) {
+            let () = msg_send![*timer.load(), invalidate];
+        }
+        let _: Vec<_> = mem::take(&mut view_state.idle_queue.lock().expect("queue"));
+
+        let tracking_area = *view_state.tracking_area.load();
+        if tracking_area != nil {
+            let () = msg_send![this as *const _, removeTrackingArea: tracking_area];
+        }
+
+        #[cfg(feature = "raw-win-handle")]
+        if let Some(parent) = &view_state.parent {
+            let window: id = msg_send![this as *const _, window];
+            if window != nil {
+                detach_child_window(window, parent);
+            }
+        }
+
+        release_display_link(view_state);
+    }
+}
+
 extern "C" fn run_idle(this: &mut Object, _: Sel) {
     let view_state = unsafe {
         let view_state: *mut c_void = *this.get_ivar("viewState");
         &mut *(view_state as *mut ViewState)
     };
+    if view_state.closed {
+        return;
+    }
     let queue: Vec<_> = mem::take(&mut view_state.idle_queue.lock().expect("queue"));
     for item in queue {
         match item {
@@ -1087,6 +1705,9 @@ extern "C" fn handle_timer(this: &mut Object, _: Sel, timer: id) {
         let view_state: *mut c_void = *this.get_ivar("viewState");
         &mut *(view_state as *mut ViewState)
     };
+    if view_state.closed {
+        return;
+    }
     let token = unsafe {
         let user_info: id = msg_send![timer, userInfo];
         msg_send![user_info, unsignedIntValue]
@@ -1113,6 +1734,174 @@ extern "C" fn show_context_menu(this: &mut Object, _: Sel, item: id) {
     }
 }
 
+/// Reads the `CGDirectDisplayID` of the screen `window` is currently on, falling
+/// back to the main display if `window` isn't on a screen yet (e.g. not yet shown).
+fn current_cg_display_id(window: id) -> u32 {
+    unsafe {
+        let screen: id = msg_send![window, screen];
+        if screen == nil {
+            return cv_display_link::CGMainDisplayID();
+        }
+        let device_description: id = msg_send![screen, deviceDescription];
+        let key = NSString::alloc(nil).init_str("NSScreenNumber").autorelease();
+        let number: id = msg_send![device_description, objectForKey: key];
+        if number == nil {
+            return cv_display_link::CGMainDisplayID();
+        }
+        msg_send![number, unsignedIntValue]
+    }
+}
+
+/// Context handed to [`display_link_callback`] as its opaque `user_info` pointer.
+///
+/// Owned by [`ViewState::display_link_context`] so its lifetime matches the link's;
+/// released alongside the link itself in [`release_display_link`].
+struct DisplayLinkContext {
+    view: WeakPtr,
+    anim_pending: Arc<AtomicBool>,
+}
+
+/// The CVDisplayLink output callback: runs on a CoreVideo-owned high-priority
+/// thread, so it may only touch `anim_pending` and hop to the main thread, never
+/// the renderer or anything else on `ViewState` directly.
+extern "C" fn display_link_callback(
+    display_link: CVDisplayLinkRef,
+    _in_now: *const c_void,
+    _in_output_time: *const c_void,
+    _flags_in: CVOptionFlags,
+    _flags_out: *mut CVOptionFlags,
+    context: *mut c_void,
+) -> CVReturn {
+    unsafe {
+        let context = &*(context as *const DisplayLinkContext);
+        if context.anim_pending.swap(false, Ordering::AcqRel) {
+            let view = *context.view.load();
+            let () = msg_send![view, performSelectorOnMainThread: sel!(redraw)
+                withObject: nil waitUntilDone: NO];
+        } else {
+            cv_display_link::CVDisplayLinkStop(display_link);
+        }
+    }
+    0 // kCVReturnSuccess
+}
+
+/// Creates `view_state.display_link` on first use and starts it if it's stopped.
+fn ensure_display_link(view_state: &mut ViewState, window: id) {
+    use cv_display_link::*;
+    if view_state.display_link.is_null() {
+        unsafe {
+            let mut link: CVDisplayLinkRef = std::ptr::null_mut();
+            let display_id = current_cg_display_id(window);
+            if CVDisplayLinkCreateWithCGDisplay(display_id, &mut link) != 0 || link.is_null() {
+                return;
+            }
+            let context = Box::new(DisplayLinkContext {
+                view: view_state.nsview.clone(),
+                anim_pending: view_state.anim_pending.clone(),
+            });
+            let context = Box::into_raw(context);
+            CVDisplayLinkSetOutputCallback(link, display_link_callback, context as *mut c_void);
+            let period = CVDisplayLinkGetNominalOutputVideoRefreshPeriod(link);
+            if period.time_scale != 0 {
+                view_state.nominal_refresh_period =
+                    Some(period.time_value as f64 / period.time_scale as f64);
+            }
+            view_state.display_link = link;
+            view_state.display_link_context = Some(Box::from_raw(context));
+        }
+    }
+    unsafe {
+        if CVDisplayLinkIsRunning(view_state.display_link) == 0 {
+            CVDisplayLinkStart(view_state.display_link);
+        }
+    }
+}
+
+/// Retargets an already-created display link to `window`'s current screen, called
+/// when the window changes screens (different refresh rate, different vblank phase).
+fn retarget_display_link(view_state: &mut ViewState, window: id) {
+    use cv_display_link::*;
+    if view_state.display_link.is_null() {
+        return;
+    }
+    unsafe {
+        let display_id = current_cg_display_id(window);
+        CVDisplayLinkSetCurrentCGDisplay(view_state.display_link, display_id);
+        let period = CVDisplayLinkGetNominalOutputVideoRefreshPeriod(view_state.display_link);
+        if period.time_scale != 0 {
+            view_state.nominal_refresh_period =
+                Some(period.time_value as f64 / period.time_scale as f64);
+        }
+    }
+}
+
+/// Stops and releases `view_state.display_link`, if one was ever created.
+fn release_display_link(view_state: &mut ViewState) {
+    use cv_display_link::*;
+    if view_state.display_link.is_null() {
+        return;
+    }
+    unsafe {
+        CVDisplayLinkStop(view_state.display_link);
+        CVDisplayLinkRelease(view_state.display_link);
+    }
+    view_state.display_link = std::ptr::null_mut();
+    view_state.display_link_context = None;
+}
+
+/// Attaches `child` to `parent` as a native child window via `addChildWindow:ordered:`,
+/// so AppKit moves `child` along with `parent` automatically instead of relying on
+/// `set_position` to manually re-offset it after the fact.
+///
+/// Requires the `raw-win-handle` feature: it's currently the only way this backend can
+/// recover `parent`'s underlying `NSView` (and from it, its `NSWindow`) from the opaque
+/// `crate::WindowHandle` stored in `ViewState::parent`. Without the feature, `parent`
+/// windows fall back to the plain position-offset behavior in `set_position`/`get_position`.
+#[cfg(feature = "raw-win-handle")]
+fn attach_child_window(child: id, parent: &crate::WindowHandle) {
+    let parent_view = match parent.raw_window_handle() {
+        RawWindowHandle::MacOS(handle) => handle.ns_view as id,
+        _ => return,
+    };
+    if parent_view == nil {
+        return;
+    }
+    unsafe {
+        let parent_window: id = msg_send![parent_view, window];
+        if parent_window == nil || parent_window == child {
+            return;
+        }
+        let () = msg_send![
+            parent_window,
+            addChildWindow: child
+            ordered: appkit::NSWindowOrderingMode::NSWindowAbove
+        ];
+    }
+}
+
+/// Detaches `child` from `parent`'s `NSWindow`, undoing [`attach_child_window`].
+///
+/// Safe to call even if `attach_child_window` returned early without attaching
+/// (e.g. the parent had no `NSWindow` yet): `removeChildWindow:` on a window that was never
+/// actually added as a child is a harmless no-op.
+#[cfg(feature = "raw-win-handle")]
+fn detach_child_window(child: id, parent: &crate::WindowHandle) {
+    let parent_view = match parent.raw_window_handle() {
+        RawWindowHandle::MacOS(handle) => handle.ns_view as id,
+        _ => return,
+    };
+    if parent_view == nil {
+        return;
+    }
+    unsafe {
+        let parent_window: id = msg_send![parent_view, window];
+        if parent_window == nil {
+            return;
+        }
+        let () = msg_send![parent_window, removeChildWindow: child];
+    }
+}
+
 extern "C" fn window_did_change_backing_properties(this: &mut Object, _: Sel, _notification: id) {
     unsafe {
         let view_state: *mut c_void = *this.get_ivar("viewState");
@@ -1120,6 +1909,7 @@ extern "C" fn window_did_change_backing_properties(this: &mut Object, _: Sel, _n
 
         let frame = NSView::frame(*(view_state).nsview.load());
         let scale = NSScreen::backingScaleFactor(*(view_state).nswindow.load()) as f64;
+        let old_scale = (*view_state).last_scale;
 
         let renderer = &mut (*view_state).renderer;
         renderer.set_size(Size::new(
@@ -1128,6 +1918,19 @@ extern "C" fn window_did_change_backing_properties(this: &mut Object, _: Sel, _n
         ));
         renderer.set_scale(scale);
         (*view_state).gl_context.context.load().update();
+
+        if scale != old_scale {
+            (*view_state).last_scale = scale;
+            (*view_state).handler.scale(Scale::new(scale, scale));
+            // The window's logical size is unchanged, but downstream layout that
+            // reads it alongside the new scale (to recompute physical-pixel
+            // quantities) needs a fresh `size` call to pick that up.
+            (*view_state)
+                .handler
+                .size(Size::new(frame.size.width, frame.size.height));
+        }
+
+        retarget_display_link(view_state, *view_state.nswindow.load());
     }
 }
 
@@ -1158,6 +1961,7 @@ extern "C" fn window_should_close(this: &mut Object, _: Sel, _window: id) -> BOO
 
 extern "C" fn window_will_close(this: &mut Object, _: Sel, _notification: id) {
     unsafe {
+        let () = msg_send![this as *const _, druidTeardown];
         let view_state: *mut c_void = *this.get_ivar("viewState");
         let view_state = &mut *(view_state as *mut ViewState);
         (*view_state).handler.destroy();
@@ -1173,6 +1977,8 @@ extern "C" fn window_did_move(this: &mut Object, _: Sel, _notification: id) {
         (*view_state)
             .handler
             .position(Point::new(rect.origin.x, rect.origin.y));
+
+        retarget_display_link(view_state, *(*view_state).nswindow.load());
     }
 }
 
@@ -1192,10 +1998,39 @@ impl WindowHandle {
     }
 
     /// Close the window.
+    ///
+    /// Drives teardown (invalidating timers, draining the idle queue, removing the
+    /// tracking area, detaching from any parent window) directly, rather than relying
+    /// solely on `windowWillClose:`, so embedded windows (no `NSWindow` of their own,
+    /// see `WindowBuilder::set_parent`) are torn down deterministically too.
+    ///
+    /// `ViewState` itself is still reclaimed by the view's `dealloc`, keyed to the
+    /// `NSView`'s own Objective-C retain count rather than an `Rc`: `druidTeardown`'s
+    /// `closed` flag makes a timer, idle callback, appearance change, or
+    /// `request_anim_frame` that arrives after teardown a no-op (see `run_idle`,
+    /// `handle_timer`, and the other early-returns on `closed` in this file). Mouse,
+    /// keyboard and `drawRect:` handlers don't check `closed`; they still reach the
+    /// `ViewState`, which stays allocated until `dealloc`.
     pub fn close(&self) {
         unsafe {
-            let window: id = msg_send![*self.nsview.load(), window];
-            let () = msg_send![window, performSelectorOnMainThread: sel!(close) withObject: nil waitUntilDone: NO];
+            let view = *self.nsview.load();
+            let () = msg_send![view, druidTeardown];
+            let window: id = msg_send![view, window];
+            if window != nil {
+                // `windowWillClose:` drives `handler.destroy()` once the close actually
+                // happens.
+                run_on_main_thread(move || unsafe {
+                    let () = msg_send![window, close];
+                });
+            } else {
+                // Embedded windows have no `NSWindow` to deliver `windowWillClose:`, so
+                // this is the only place `handler.destroy()` can be driven from.
+                let view_obj = (*view).as_ref().unwrap();
+                let view_state: *mut c_void = *view_obj.get_ivar("viewState");
+                let view_state = &mut *(view_state as *mut ViewState);
+                view_state.handler.destroy();
+                let () = msg_send![view, removeFromSuperview];
+            }
         }
     }
 
@@ -1215,12 +2050,30 @@ impl WindowHandle {
         }
     }
 
+    /// Not main-thread-restricted by contract (unlike most of this backend's other
+    /// `WindowHandle` methods, callers are free to invoke this from a renderer
+    /// thread), so the `ViewState` fields `ensure_display_link` touches are
+    /// marshaled onto the main thread the same way as `set_title`/`resizable`/etc.
+    /// do, rather than mutated here directly: those same fields are also mutated
+    /// by `retarget_display_link`/`release_display_link`, which run on the main
+    /// thread via AppKit delegate callbacks, and mutating them unsynchronized from
+    /// both places at once would race.
     pub fn request_anim_frame(&self) {
-        unsafe {
-            // TODO: synchronize with screen refresh rate using CVDisplayLink instead.
-            let () = msg_send![*self.nsview.load(), performSelectorOnMainThread: sel!(redraw)
-                withObject: nil waitUntilDone: NO];
-        }
+        let nsview = self.nsview.clone();
+        run_on_main_thread(move || unsafe {
+            if let Some(view) = nsview.load().as_ref() {
+                let view_state: *mut c_void = *view.get_ivar("viewState");
+                let view_state = &mut *(view_state as *mut ViewState);
+                if view_state.closed {
+                    return;
+                }
+
+                view_state.anim_pending.store(true, Ordering::Release);
+
+                let window: id = msg_send![view as *const _, window];
+                ensure_display_link(view_state, window);
+            }
+        });
     }
 
     // Request invalidation of the entire window contents.
@@ -1243,6 +2096,12 @@ impl WindowHandle {
         }
     }
 
+    /// Toggles AppKit's coalescing of consecutive mouse-move events; see
+    /// [`WindowBuilder::set_mouse_coalescing`] for when to disable it.
+    pub fn set_mouse_coalescing(&self, enabled: bool) {
+        set_mouse_coalescing_enabled(enabled);
+    }
+
     pub fn set_cursor(&mut self, cursor: &Cursor) {
         unsafe {
             let nscursor = class!(NSCursor);
@@ -1256,16 +2115,77 @@ impl WindowHandle {
                 Cursor::NotAllowed => msg_send![nscursor, operationNotAllowedCursor],
                 Cursor::ResizeLeftRight => msg_send![nscursor, resizeLeftRightCursor],
                 Cursor::ResizeUpDown => msg_send![nscursor, resizeUpDownCursor],
-                // TODO: support custom cursors
-                Cursor::Custom(_) => msg_send![nscursor, arrowCursor],
+                Cursor::Custom(custom) => *custom.0,
             };
             let () = msg_send![cursor, set];
         }
     }
 
-    pub fn make_cursor(&self, _cursor_desc: &CursorDesc) -> Option<Cursor> {
-        tracing::warn!("Custom cursors are not yet supported in the macOS backend");
-        None
+    pub fn make_cursor(&self, desc: &CursorDesc) -> Option<Cursor> {
+        unsafe {
+            let width = desc.image.width() as NSInteger;
+            let height = desc.image.height() as NSInteger;
+            let pixels = desc.image.raw_pixels();
+
+            let bitmap: id = msg_send![class!(NSBitmapImageRep), alloc];
+            let bitmap: id = msg_send![bitmap,
+                initWithBitmapDataPlanes: std::ptr::null_mut::<*mut u8>()
+                pixelsWide: width
+                pixelsHigh: height
+                bitsPerSample: 8_i64
+                samplesPerPixel: 4_i64
+                hasAlpha: YES
+                isPlanar: NO
+                colorSpaceName: make_nsstring("NSDeviceRGBColorSpace")
+                bitmapFormat: 0_u64
+                bytesPerRow: (width * 4)
+                bitsPerPixel: 32_i64
+            ];
+            if bitmap == nil {
+                tracing::warn!("Failed to allocate NSBitmapImageRep for custom cursor");
+                return None;
+            }
+
+            let dest: *mut u8 = msg_send![bitmap, bitmapData];
+            std::ptr::copy_nonoverlapping(pixels.as_ptr(), dest, pixels.len());
+
+            // `desc.image`'s pixels are assumed to already be captured at the
+            // window's backing scale factor, so the bitmap rep (and the `NSImage`
+            // wrapping it) is given a logical size scaled back down to match;
+            // otherwise a hi-dpi source buffer would show up twice as large as
+            // intended instead of crisp at its native resolution.
+            let window: id = msg_send![*self.nsview.load(), window];
+            let scale = if window == nil {
+                1.0
+            } else {
+                NSScreen::backingScaleFactor(window) as f64
+            };
+            let size = NSSize::new(width as f64 / scale, height as f64 / scale);
+            let () = msg_send![bitmap, setSize: size];
+
+            let image: id = msg_send![class!(NSImage), alloc];
+            let image: id = msg_send![image, initWithSize: size];
+            let () = msg_send![image, addRepresentation: bitmap];
+            let () = msg_send![bitmap, release];
+
+            let hot_spot = NSPoint::new(desc.hot.x / scale, desc.hot.y / scale);
+            let cursor: id = msg_send![class!(NSCursor), alloc];
+            let cursor: id = msg_send![cursor, initWithImage: image hotSpot: hot_spot];
+            let () = msg_send![image, release];
+            if cursor == nil {
+                return None;
+            }
+
+            Some(Cursor::Custom(CustomCursor(StrongPtr::new(cursor))))
+        }
+    }
+
+    /// Returns the view's current light/dark appearance.
+    ///
+    /// Lets a newly created window query its initial appearance up front, rather
+    /// than waiting for the first `viewDidChangeEffectiveAppearance` callback.
+    pub fn current_appearance(&self) -> Appearance {
+        unsafe { current_appearance(*self.nsview.load()) }
     }
 
     pub fn request_timer(&self, deadline: std::time::Instant) -> TimerToken {
@@ -1280,6 +2200,15 @@ impl WindowHandle {
             let timer: id = msg_send![nstimer, timerWithTimeInterval: ti target: view selector: selector userInfo: user_info repeats: NO];
             let runloop: id = msg_send![class!(NSRunLoop), currentRunLoop];
             let () = msg_send![runloop, addTimer: timer forMode: NSRunLoopCommonModes];
+
+            let view_obj = (*view).as_ref().unwrap();
+            let view_state: *mut c_void = *view_obj.get_ivar("viewState");
+            let view_state = &mut *(view_state as *mut ViewState);
+            if view_state.closed {
+                let () = msg_send![timer, invalidate];
+            } else {
+                view_state.timers.push(WeakPtr::new(timer));
+            }
         }
         token
     }
@@ -1293,6 +2222,40 @@ impl WindowHandle {
         }
     }
 
+    /// Repositions the standard close/minimize/zoom buttons by `inset` from the
+    /// window's top-left corner.
+    ///
+    /// See [`WindowBuilder::set_titlebar_button_inset`]. Combine with
+    /// [`WindowHandle::set_dragable_area`] to reserve a draggable strip for a
+    /// custom-drawn titlebar once the native buttons are out of the way.
+    pub fn set_titlebar_button_inset(&self, inset: Vec2) {
+        unsafe {
+            let window: id = msg_send![*self.nsview.load(), window];
+            if window == nil {
+                return;
+            }
+            // NSWindowCloseButton, NSWindowMiniaturizeButton, NSWindowZoomButton;
+            // not exposed by the `cocoa` crate as an enum, so sent raw. All three
+            // share the same superview (the titlebar view), so it must only be
+            // repositioned once, not once per button.
+            for button_kind in [0_u64, 1, 2] {
+                let button: id = msg_send![window, standardWindowButton: button_kind];
+                if button == nil {
+                    continue;
+                }
+                let superview: id = msg_send![button, superview];
+                if superview == nil {
+                    continue;
+                }
+                let superview_frame: NSRect = msg_send![superview, frame];
+                let new_origin =
+                    NSPoint::new(inset.x, superview_frame.origin.y - inset.y);
+                let () = msg_send![superview, setFrameOrigin: new_origin];
+                break;
+            }
+        }
+    }
+
     pub fn set_dragable_area(&self, area: Region) {
         let view = self.nsview.load();
         unsafe {
@@ -1321,6 +2284,26 @@ impl WindowHandle {
         }
     }
 
+    /// Enables or disables IME composition for this window.
+    ///
+    /// When disabled, the view should decline marked text and deliver every
+    /// keystroke as plain committed input instead of starting a composition
+    /// session; see [`ViewState::ime_allowed`] for the current limits of that
+    /// wiring in this tree.
+    pub fn set_ime_allowed(&self, allowed: bool) {
+        unsafe {
+            if let Some(view) = self.nsview.load().as_ref() {
+                let state: *mut c_void = *view.get_ivar("viewState");
+                let state = &mut (*(state as *mut ViewState));
+                state.ime_allowed = allowed;
+                if !allowed {
+                    let input_context: id = msg_send![*self.nsview.load(), inputContext];
+                    let _: () = msg_send![input_context, discardMarkedText];
+                }
+            }
+        }
+    }
+
     pub fn set_focused_text_field(&self, active_field: Option<TextFieldToken>) {
         unsafe {
             if let Some(view) = self.nsview.load().as_ref() {
@@ -1398,21 +2381,57 @@ impl WindowHandle {
 
     /// Set the title for this menu.
     pub fn set_title(&self, title: &str) {
+        let nsview = self.nsview.clone();
+        let title = title.to_string();
+        run_on_main_thread(move || unsafe {
+            let window: id = msg_send![*nsview.load(), window];
+            let title = make_nsstring(&title);
+            window.setTitle_(title);
+        });
+    }
+
+    /// Shows or hides the native titlebar at runtime.
+    ///
+    /// Mirrors the style-mask setup [`WindowBuilder::build`] applies when
+    /// [`WindowBuilder::show_titlebar`] is set to `false` at creation: an `NSTitled`
+    /// window with `NSFullSizeContentView`/`NSUnifiedTitleAndToolbar` added, a
+    /// transparent titlebar, and a hidden title, reclaiming the titlebar's space for
+    /// content (see [`WindowHandle::content_insets`], which reflects this live).
+    /// Combine with [`WindowHandle::handle_titlebar`] to keep the window draggable
+    /// once the native titlebar is gone, and [`WindowHandle::set_titlebar_button_inset`]
+    /// to reposition the traffic-light buttons over the new content area.
+    pub fn show_titlebar(&self, show_titlebar: bool) {
         unsafe {
             let window: id = msg_send![*self.nsview.load(), window];
-            let title = make_nsstring(title);
-            window.setTitle_(title);
+            if window == nil {
+                return;
+            }
+            let mut style_mask: NSWindowStyleMask = msg_send![window, styleMask];
+            if show_titlebar {
+                style_mask &= !(NSWindowStyleMask::NSFullSizeContentViewWindowMask
+                    | NSWindowStyleMask::NSUnifiedTitleAndToolbarWindowMask);
+            } else {
+                style_mask |= NSWindowStyleMask::NSFullSizeContentViewWindowMask
+                    | NSWindowStyleMask::NSUnifiedTitleAndToolbarWindowMask;
+            }
+            let () = msg_send![window, setStyleMask: style_mask];
+
+            window.setTitlebarAppearsTransparent_(if show_titlebar { NO } else { YES });
+            window.setTitleVisibility_(if show_titlebar {
+                appkit::NSWindowTitleVisibility::NSWindowTitleVisible
+            } else {
+                appkit::NSWindowTitleVisibility::NSWindowTitleHidden
+            });
         }
     }
 
-    // TODO: Implement this
-    pub fn show_titlebar(&self, _show_titlebar: bool) {}
-
     // Need to translate mac y coords, as they start from bottom left
     pub fn set_position(&self, mut position: Point) {
-        // TODO: Maybe @cmyr can get this into a state where modal windows follow the parent?
-        // There is an API to do child windows, (https://developer.apple.com/documentation/appkit/nswindow/1419152-addchildwindow)
-        // but I have no good way of testing and making sure this works.
+        // `position` is relative to `state.parent`, if any, so it's converted to an
+        // absolute position here. `addChildWindow:` (wired up in `build` via
+        // `attach_child_window`) makes AppKit carry this window along whenever the
+        // parent moves, but it doesn't affect coordinate translation, so this offset
+        // is still required whenever `set_position` itself is called.
         unsafe {
             if let Some(view) = self.nsview.load().as_ref() {
                 let state: *mut c_void = *view.get_ivar("viewState");
@@ -1480,11 +2499,12 @@ impl WindowHandle {
     }
 
     fn set_level(&self, level: WindowLevel) {
-        unsafe {
-            let level = levels::as_raw_window_level(level);
-            let window: id = msg_send![*self.nsview.load(), window];
+        let level = levels::as_raw_window_level(level);
+        let nsview = self.nsview.clone();
+        run_on_main_thread(move || unsafe {
+            let window: id = msg_send![*nsview.load(), window];
             let () = msg_send![window, setLevel: level];
-        }
+        });
     }
 
     pub fn set_size(&self, size: Size) {
@@ -1524,46 +2544,47 @@ impl WindowHandle {
 
     pub fn set_window_state(&mut self, state: WindowState) {
         let cur_state = self.get_window_state();
-        unsafe {
-            let window: id = msg_send![*self.nsview.load(), window];
+        let nsview = self.nsview.clone();
+        run_on_main_thread(move || unsafe {
+            let window: id = msg_send![*nsview.load(), window];
             match (state, cur_state) {
                 (s1, s2) if s1 == s2 => (),
                 (WindowState::Minimized, _) => {
-                    let () = msg_send![
-                        window,
-                        performSelectorOnMainThread: sel!(performMiniaturize:) withObject: nil waitUntilDone: NO
-                    ];
+                    let () = msg_send![window, performMiniaturize: nil];
                 }
                 (WindowState::Maximized, _) => {
-                    let () = msg_send![
-                        window,
-                        performSelectorOnMainThread: sel!(performZoom:) withObject: nil waitUntilDone: NO
-                    ];
+                    let () = msg_send![window, performZoom: nil];
                 }
                 (WindowState::Restored, WindowState::Maximized) => {
-                    let () = msg_send![
-                        window,
-                        performSelectorOnMainThread: sel!(performZoom:) withObject: nil waitUntilDone: NO
-                    ];
+                    let () = msg_send![window, performZoom: nil];
                 }
                 (WindowState::Restored, WindowState::Minimized) => {
-                    let () = msg_send![
-                        window,
-                        performSelectorOnMainThread: sel!(deminiaturize:) withObject: nil waitUntilDone: NO
-                    ];
+                    let () = msg_send![window, deminiaturize: nil];
                 }
                 (WindowState::Restored, WindowState::Restored) => {} // Can't be reached
             }
-        }
+        });
     }
 
-    pub fn handle_titlebar(&self, _val: bool) {
-        tracing::warn!("WindowHandle::handle_titlebar is currently unimplemented for Mac.");
+    /// When `val` is true, a left-click anywhere in the content view starts a window
+    /// drag via `performWindowDragWithEvent:`, the same as dragging a native titlebar.
+    /// Meant for windows built with `show_titlebar(false)` that otherwise have no
+    /// titlebar left to drag from; see [`WindowHandle::set_dragable_area`] if only
+    /// part of the content, rather than all of it, should be draggable.
+    pub fn handle_titlebar(&self, val: bool) {
+        unsafe {
+            if let Some(view) = self.nsview.load().as_ref() {
+                let state: *mut c_void = *view.get_ivar("viewState");
+                let state = &mut (*(state as *mut ViewState));
+                state.handle_titlebar = val;
+            }
+        }
     }
 
     pub fn resizable(&self, resizable: bool) {
-        unsafe {
-            let window: id = msg_send![*self.nsview.load(), window];
+        let nsview = self.nsview.clone();
+        run_on_main_thread(move || unsafe {
+            let window: id = msg_send![*nsview.load(), window];
             let mut style_mask: NSWindowStyleMask = window.styleMask();
 
             if resizable {
@@ -1573,13 +2594,13 @@ impl WindowHandle {
             }
 
             window.setStyleMask_(style_mask);
-        }
+        });
     }
 
     pub fn set_menu(&self, menu: Menu) {
-        unsafe {
+        run_on_main_thread(move || unsafe {
             NSApp().setMainMenu_(menu.menu);
-        }
+        });
     }
 
     //FIXME: we should be using the x, y values passed by the caller, but then
@@ -1616,8 +2637,22 @@ impl WindowHandle {
 
     /// Get the `Scale` of the window.
     pub fn get_scale(&self) -> Result<Scale, Error> {
-        // TODO: Get actual Scale
-        Ok(Scale::new(1.0, 1.0))
+        unsafe {
+            let window: id = msg_send![*self.nsview.load(), window];
+            let factor = if window != nil {
+                NSScreen::backingScaleFactor(window)
+            } else {
+                // The window may not be on screen yet; fall back to the main
+                // screen's factor rather than assuming 1x.
+                let screen: id = msg_send![class!(NSScreen), mainScreen];
+                if screen == nil {
+                    1.0
+                } else {
+                    NSScreen::backingScaleFactor(screen)
+                }
+            } as f64;
+            Ok(Scale::new(factor, factor))
+        }
     }
 
     pub fn make_current(&self) {
@@ -11,7 +11,7 @@ use winapi::{
         windef::{HDC, HGLRC, HWND},
     },
     um::{
-        libloaderapi::{GetModuleHandleW, GetProcAddress, LoadLibraryW},
+        libloaderapi::{FreeLibrary, GetModuleHandleW, GetProcAddress, LoadLibraryW},
         wingdi::{
             ChoosePixelFormat, DescribePixelFormat, GetPixelFormat, SetPixelFormat, SwapBuffers,
             PFD_DOUBLEBUFFER, PFD_DRAW_TO_WINDOW, PFD_GENERIC_FORMAT, PFD_MAIN_PLANE, PFD_STEREO,
@@ -80,20 +80,70 @@ impl<'a, 'b> Drop for CurrentContextGuard<'a, 'b> {
     }
 }
 
-#[derive(Debug)]
 pub struct Context {
-    context: ContextWrapper,
-
-    hdc: HDC,
-
-    /// Bound to `opengl32.dll`.
-    ///
-    /// `wglGetProcAddress` returns null for GL 1.1 functions because they are
-    ///  already defined by the system. This module contains them.
-    gl_library: HMODULE,
+    backend: ContextBackend,
 
     /// The pixel format that has been used to create this context.
     pixel_format: PixelFormat,
+
+    /// The API this context was actually built against, as reported by
+    /// [`create_context`] — `Api::OpenGlEs` only if a
+    /// `WGL_EXT_create_context_es2_profile` context was actually created,
+    /// `Api::OpenGl` otherwise.
+    api: Api,
+}
+
+/// The two ways a [`Context`] can be backed: a real WGL context bound to
+/// either a window's HDC or an off-screen pbuffer, or (when neither
+/// `WGL_ARB_pbuffer` nor a window is available) a software OSMesa context
+/// rendering into a caller-owned buffer.
+enum ContextBackend {
+    Wgl {
+        context: ContextWrapper,
+
+        hdc: HDC,
+
+        /// Bound to `opengl32.dll`.
+        ///
+        /// `wglGetProcAddress` returns null for GL 1.1 functions because they
+        /// are already defined by the system. This module contains them.
+        gl_library: HMODULE,
+
+        /// The off-screen drawable `hdc` was obtained from, for a headless
+        /// context created via [`Context::new_headless`]. `None` for a
+        /// window-backed context, whose HDC is owned by the caller's window.
+        pbuffer: Option<PbufferWrapper>,
+
+        /// The WGL extension functions loaded for this context, kept around
+        /// (alongside [`SwapControlSupport`]) so [`Context::set_swap_interval`]
+        /// and [`Context::get_swap_interval`] can call `wglSwapIntervalEXT`/
+        /// `wglGetSwapIntervalEXT` after creation.
+        extra: glutin_wgl_sys::wgl_extra::Wgl,
+
+        /// Which swap-control extensions were detected while creating this
+        /// context, so [`Context::set_swap_interval`] and
+        /// [`Context::get_swap_interval`] don't need to re-query the
+        /// extension string on every call.
+        swap_control: SwapControlSupport,
+    },
+    OsMesa(OsMesaWrapper),
+}
+
+/// Which `WGL_EXT_swap_control*` extensions a context's driver exposes.
+#[derive(Debug, Clone, Copy, Default)]
+struct SwapControlSupport {
+    /// `WGL_EXT_swap_control`: `wglSwapIntervalEXT`/`wglGetSwapIntervalEXT`
+    /// are available at all.
+    swap_control: bool,
+    /// `WGL_EXT_swap_control_tear`: a negative interval (late-swap tearing)
+    /// is accepted by `wglSwapIntervalEXT`.
+    tear: bool,
+}
+
+impl std::fmt::Debug for Context {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Context").field("pixel_format", &self.pixel_format).finish()
+    }
 }
 
 /// A simple wrapper that destroys the window when it is destroyed.
@@ -132,7 +182,7 @@ impl Context {
     #[inline]
     pub unsafe fn new(
         pf_reqs: &PixelFormatRequirements,
-        opengl: &GlAttributes,
+        opengl: &GlAttributes<&Context>,
         win: HWND,
     ) -> Result<Context, Error> {
         let hdc = GetDC(win);
@@ -170,8 +220,14 @@ impl Context {
         let mut pixel_format_id = GetPixelFormat(hdc);
         if pixel_format_id == 0 {
             let id = if use_arb_for_pixel_format {
-                choose_arb_pixel_format_id(&extra_functions, &extensions, hdc, pf_reqs)
-                    .map_err(|_| anyhow::anyhow!("no avaible pixel format"))?
+                choose_arb_pixel_format_id(
+                    &extra_functions,
+                    &extensions,
+                    hdc,
+                    pf_reqs,
+                    SurfaceType::Window,
+                )
+                .map_err(|_| anyhow::anyhow!("no avaible pixel format"))?
             } else {
                 choose_native_pixel_format_id(hdc, pf_reqs)
                     .map_err(|_| anyhow::anyhow!("no avaible pixel format"))?
@@ -182,104 +238,295 @@ impl Context {
         }
 
         let pixel_format = if use_arb_for_pixel_format {
-            choose_arb_pixel_format(&extra_functions, &extensions, hdc, pixel_format_id)
+            choose_arb_pixel_format(&extra_functions, &extensions, hdc, pixel_format_id, pf_reqs)
                 .map_err(|_| anyhow::anyhow!("no avaible pixel format"))?
         } else {
             choose_native_pixel_format(hdc, pf_reqs, pixel_format_id)
                 .map_err(|_| anyhow::anyhow!("no avaible pixel format"))?
         };
 
-        // creating the OpenGL context
-        let context = create_context(
+        // creating the OpenGL context, joining the sharing context's object
+        // namespace if one was requested. The shared `Context` must outlive
+        // the one being created here, which the `&'a Context` borrow in
+        // `GlAttributes<&'a Context>` enforces.
+        let shared = opengl.sharing.map(|ctx| ctx.get_hglrc());
+        let (context, api) = create_context(
             Some((&extra_functions, pf_reqs, opengl, &extensions)),
             win,
             hdc,
+            shared,
         )?;
 
         // loading the opengl32 module
         let gl_library = load_opengl32_dll()?;
 
         // handling vsync
-        if extensions
-            .split(' ')
-            .find(|&i| i == "WGL_EXT_swap_control")
-            .is_some()
-        {
+        let swap_control = SwapControlSupport {
+            swap_control: extensions.split(' ').any(|i| i == "WGL_EXT_swap_control"),
+            tear: extensions.split(' ').any(|i| i == "WGL_EXT_swap_control_tear"),
+        };
+        if swap_control.swap_control {
             let _guard = CurrentContextGuard::make_current(hdc, context.0)?;
 
-            if extra_functions.SwapIntervalEXT(if opengl.vsync { 1 } else { 0 }) == 0 {
+            let interval = swap_interval_to_raw(opengl.swap_interval, swap_control.tear)?;
+            if extra_functions.SwapIntervalEXT(interval) == 0 {
                 return Err(anyhow::anyhow!("wglSwapIntervalEXT failed".to_string(),).into());
             }
         }
 
         Ok(Context {
-            context,
+            backend: ContextBackend::Wgl {
+                context,
+                hdc,
+                gl_library,
+                pbuffer: None,
+                extra: extra_functions,
+                swap_control,
+            },
+            pixel_format,
+            api,
+        })
+    }
+
+    /// Builds a windowless `Context` for off-screen rendering (CI, thumbnail
+    /// generation, tests), of the requested `(width, height)`.
+    ///
+    /// When the driver exposes `WGL_ARB_pbuffer` and `WGL_ARB_pixel_format`,
+    /// this creates a pbuffer-backed drawable and builds a real WGL context
+    /// against it, same as [`Context::new`] but without a window. Drivers
+    /// that lack pbuffer support fall back to a software [`OSMesa`] context
+    /// rendering into an owned buffer; `get_api` still reports correctly in
+    /// that case, but performance is much lower.
+    ///
+    /// `swap_buffers` is a no-op in both cases, since there is no drawable
+    /// for the system compositor to present.
+    ///
+    /// [`OSMesa`]: https://docs.mesa3d.org/osmesa.html
+    pub unsafe fn new_headless(
+        pf_reqs: &PixelFormatRequirements,
+        opengl: &GlAttributes<&Context>,
+        size: (u32, u32),
+    ) -> Result<Context, Error> {
+        let dummy_win = create_hidden_window()?;
+
+        // loading the functions that are not guaranteed to be supported
+        let extra_functions = load_extra_functions(dummy_win.0)?;
+
+        // getting the list of the supported extensions
+        let extensions = if extra_functions.GetExtensionsStringARB.is_loaded() {
+            let data = extra_functions.GetExtensionsStringARB(dummy_win.1 as *const _);
+            let data = CStr::from_ptr(data).to_bytes().to_vec();
+            String::from_utf8(data).unwrap()
+        } else if extra_functions.GetExtensionsStringEXT.is_loaded() {
+            let data = extra_functions.GetExtensionsStringEXT();
+            let data = CStr::from_ptr(data).to_bytes().to_vec();
+            String::from_utf8(data).unwrap()
+        } else {
+            format!("")
+        };
+
+        let has_pbuffer = extensions.split(' ').any(|i| i == "WGL_ARB_pbuffer")
+            && extensions.split(' ').any(|i| i == "WGL_ARB_pixel_format");
+
+        if !has_pbuffer {
+            let osmesa = create_osmesa_context(size)?;
+            return Ok(Context {
+                backend: ContextBackend::OsMesa(osmesa),
+                pixel_format: osmesa_pixel_format(),
+                api: Api::OpenGl,
+            });
+        }
+
+        let pixel_format_id = choose_arb_pixel_format_id(
+            &extra_functions,
+            &extensions,
+            dummy_win.1,
+            pf_reqs,
+            SurfaceType::Pbuffer,
+        )
+        .map_err(|_| anyhow::anyhow!("no avaible pbuffer pixel format"))?;
+
+        let pixel_format = choose_arb_pixel_format(
+            &extra_functions,
+            &extensions,
+            dummy_win.1,
+            pixel_format_id,
+            pf_reqs,
+        )
+        .map_err(|_| anyhow::anyhow!("no avaible pbuffer pixel format"))?;
+
+        let (pbuffer, hdc) =
+            create_pbuffer(&extra_functions, dummy_win.1, pixel_format_id, size)?;
+
+        let shared = opengl.sharing.map(|ctx| ctx.get_hglrc());
+        let (context, api) = create_context(
+            Some((&extra_functions, pf_reqs, opengl, &extensions)),
+            std::ptr::null_mut(),
             hdc,
-            gl_library,
+            shared,
+        )?;
+
+        let gl_library = load_opengl32_dll()?;
+
+        let swap_control = SwapControlSupport {
+            swap_control: extensions.split(' ').any(|i| i == "WGL_EXT_swap_control"),
+            tear: extensions.split(' ').any(|i| i == "WGL_EXT_swap_control_tear"),
+        };
+
+        Ok(Context {
+            backend: ContextBackend::Wgl {
+                context,
+                hdc,
+                gl_library,
+                pbuffer: Some(PbufferWrapper {
+                    pbuffer,
+                    hdc,
+                    extra: extra_functions.clone(),
+                    _dummy_win: dummy_win,
+                }),
+                extra: extra_functions,
+                swap_control,
+            },
             pixel_format,
+            api,
         })
     }
 
-    /// Returns the raw HGLRC.
+    /// Returns the raw HGLRC, or a null HGLRC for a software [`OSMesa`]-backed
+    /// headless context, which has no HGLRC to share.
+    ///
+    /// [`OSMesa`]: https://docs.mesa3d.org/osmesa.html
     #[inline]
     pub fn get_hglrc(&self) -> HGLRC {
-        self.context.0
+        match &self.backend {
+            ContextBackend::Wgl { context, .. } => context.0,
+            ContextBackend::OsMesa(_) => std::ptr::null_mut(),
+        }
     }
 
     #[inline]
     pub unsafe fn make_current(&self) -> Result<(), Error> {
-        if glutin_wgl_sys::wgl::MakeCurrent(self.hdc as *const _, self.context.0 as *const _) != 0 {
-            Ok(())
-        } else {
-            Err(anyhow::anyhow!(std::io::Error::last_os_error()).into())
+        match &self.backend {
+            ContextBackend::Wgl { context, hdc, .. } => {
+                if glutin_wgl_sys::wgl::MakeCurrent(*hdc as *const _, context.0 as *const _) != 0 {
+                    Ok(())
+                } else {
+                    Err(anyhow::anyhow!(std::io::Error::last_os_error()).into())
+                }
+            }
+            ContextBackend::OsMesa(osmesa) => osmesa.make_current(),
         }
     }
 
     #[inline]
     pub unsafe fn make_not_current(&self) -> Result<(), Error> {
-        if self.is_current()
-            && glutin_wgl_sys::wgl::MakeCurrent(self.hdc as *const _, std::ptr::null()) != 0
-        {
-            Ok(())
-        } else {
-            Err(anyhow::anyhow!(std::io::Error::last_os_error()).into())
+        match &self.backend {
+            ContextBackend::Wgl { hdc, .. } => {
+                if self.is_current()
+                    && glutin_wgl_sys::wgl::MakeCurrent(*hdc as *const _, std::ptr::null()) != 0
+                {
+                    Ok(())
+                } else {
+                    Err(anyhow::anyhow!(std::io::Error::last_os_error()).into())
+                }
+            }
+            // OSMesa has no "not current" state short of making another
+            // context current; nothing to release here.
+            ContextBackend::OsMesa(_) => Ok(()),
         }
     }
 
     #[inline]
     pub fn is_current(&self) -> bool {
-        unsafe { glutin_wgl_sys::wgl::GetCurrentContext() == self.context.0 as *const raw::c_void }
+        match &self.backend {
+            ContextBackend::Wgl { context, .. } => unsafe {
+                glutin_wgl_sys::wgl::GetCurrentContext() == context.0 as *const raw::c_void
+            },
+            ContextBackend::OsMesa(_) => true,
+        }
     }
 
     pub fn get_proc_address(&self, addr: &str) -> *const core::ffi::c_void {
         let addr = CString::new(addr.as_bytes()).unwrap();
         let addr = addr.as_ptr();
 
-        unsafe {
-            let p = glutin_wgl_sys::wgl::GetProcAddress(addr) as *const core::ffi::c_void;
-            if !p.is_null() {
-                return p;
-            }
-            GetProcAddress(self.gl_library, addr) as *const _
+        match &self.backend {
+            ContextBackend::Wgl { gl_library, .. } => unsafe {
+                let p = glutin_wgl_sys::wgl::GetProcAddress(addr) as *const core::ffi::c_void;
+                if !p.is_null() {
+                    return p;
+                }
+                GetProcAddress(*gl_library, addr) as *const _
+            },
+            ContextBackend::OsMesa(osmesa) => unsafe { osmesa.get_proc_address(addr) },
         }
     }
 
     #[inline]
     pub fn swap_buffers(&self) -> Result<(), Error> {
-        // TODO: decide how to handle the error
-        // if unsafe { SwapBuffers(self.hdc) } != 0 {
-        // Ok(())
-        // } else {
-        // Err(ContextError::IoError(std::io::Error::last_os_error()))
-        // }
-        unsafe { SwapBuffers(self.hdc) };
-        Ok(())
+        match &self.backend {
+            // A pbuffer has no front/back buffer for the compositor to
+            // present, so swapping is a no-op.
+            ContextBackend::Wgl { hdc, pbuffer: None, .. } => {
+                unsafe { SwapBuffers(*hdc) };
+                Ok(())
+            }
+            ContextBackend::Wgl { pbuffer: Some(_), .. } | ContextBackend::OsMesa(_) => Ok(()),
+        }
+    }
+
+    /// Sets the swap interval: `0` disables vsync, a positive `n` waits for
+    /// `n` display refreshes between swaps, and `-n` requests adaptive
+    /// ("late swap tearing") vsync, which behaves like `n` except that a
+    /// frame missing its deadline swaps immediately (and tears) instead of
+    /// stalling for another refresh. `-n` is only accepted when the driver
+    /// exposes `WGL_EXT_swap_control_tear`; it is rejected for a software
+    /// [`OSMesa`](https://docs.mesa3d.org/osmesa.html)-backed headless
+    /// context or one whose driver lacks `WGL_EXT_swap_control`.
+    pub unsafe fn set_swap_interval(&self, interval: i32) -> Result<(), Error> {
+        match &self.backend {
+            ContextBackend::Wgl { context, hdc, extra, swap_control, .. } => {
+                if !swap_control.swap_control {
+                    return Err(anyhow::anyhow!("WGL_EXT_swap_control not supported").into());
+                }
+                if interval < 0 && !swap_control.tear {
+                    return Err(
+                        anyhow::anyhow!("WGL_EXT_swap_control_tear not supported").into()
+                    );
+                }
+
+                let _guard = CurrentContextGuard::make_current(*hdc, context.0)?;
+                if extra.SwapIntervalEXT(interval as raw::c_int) == 0 {
+                    return Err(anyhow::anyhow!(
+                        "wglSwapIntervalEXT failed: {}",
+                        std::io::Error::last_os_error()
+                    )
+                    .into());
+                }
+                Ok(())
+            }
+            ContextBackend::OsMesa(_) => {
+                Err(anyhow::anyhow!("swap interval is not meaningful for an OSMesa context")
+                    .into())
+            }
+        }
+    }
+
+    /// Returns the swap interval last set via [`Context::set_swap_interval`]
+    /// (or at context creation), or `0` if the driver doesn't support
+    /// `WGL_EXT_swap_control`.
+    pub fn get_swap_interval(&self) -> i32 {
+        match &self.backend {
+            ContextBackend::Wgl { extra, swap_control, .. } if swap_control.swap_control => {
+                unsafe { extra.GetSwapIntervalEXT() as i32 }
+            }
+            ContextBackend::Wgl { .. } | ContextBackend::OsMesa(_) => 0,
+        }
     }
 
     #[inline]
     pub fn get_api(&self) -> Api {
-        // FIXME: can be opengl es
-        Api::OpenGl
+        self.api
     }
 
     #[inline]
@@ -398,7 +645,7 @@ unsafe fn load_extra_functions(win: HWND) -> Result<glutin_wgl_sys::wgl_extra::W
     }
 
     // creating the dummy OpenGL context and making it current
-    let dummy_ctx = create_context(None, dummy_win.0, dummy_win.1)?;
+    let (dummy_ctx, _) = create_context(None, dummy_win.0, dummy_win.1, None)?;
     let _current_context = CurrentContextGuard::make_current(dummy_win.1, dummy_ctx.0)?;
 
     // loading the extra WGL functions
@@ -409,6 +656,273 @@ unsafe fn load_extra_functions(win: HWND) -> Result<glutin_wgl_sys::wgl_extra::W
     }))
 }
 
+/// Creates a tiny invisible window with its own window class, for use as the
+/// basis of a headless [`Context`] (see [`Context::new_headless`]) where
+/// there is no real application window to attach a pbuffer's HDC to.
+unsafe fn create_hidden_window() -> Result<WindowWrapper, Error> {
+    let class_name = OsStr::new("Druid Headless GL Class")
+        .encode_wide()
+        .chain(Some(0).into_iter())
+        .collect::<Vec<_>>();
+
+    let instance = GetModuleHandleW(std::ptr::null());
+    let mut class: WNDCLASSEXW = std::mem::zeroed();
+    class.cbSize = std::mem::size_of::<WNDCLASSEXW>() as UINT;
+    class.lpszClassName = class_name.as_ptr();
+    class.lpfnWndProc = Some(DefWindowProcW);
+    class.hInstance = instance;
+
+    // multiple registrations of the window class trigger an error which we
+    // want to ignore silently (e.g. when creating more than one headless
+    // context)
+    RegisterClassExW(&class);
+
+    let title = OsStr::new("druid headless gl window")
+        .encode_wide()
+        .chain(Some(0).into_iter())
+        .collect::<Vec<_>>();
+    let win = CreateWindowExW(
+        WS_EX_APPWINDOW,
+        class_name.as_ptr(),
+        title.as_ptr() as LPCWSTR,
+        WS_POPUP | WS_CLIPSIBLINGS | WS_CLIPCHILDREN,
+        CW_USEDEFAULT,
+        CW_USEDEFAULT,
+        1,
+        1,
+        std::ptr::null_mut(),
+        std::ptr::null_mut(),
+        instance,
+        std::ptr::null_mut(),
+    );
+
+    if win.is_null() {
+        return Err(anyhow::anyhow!(
+            "CreateWindowEx function failed: {}",
+            std::io::Error::last_os_error()
+        )
+        .into());
+    }
+
+    let hdc = GetDC(win);
+    if hdc.is_null() {
+        return Err(anyhow::anyhow!(
+            "GetDC function failed: {}",
+            std::io::Error::last_os_error()
+        )
+        .into());
+    }
+
+    Ok(WindowWrapper(win, hdc))
+}
+
+/// Which kind of drawable a pixel format chosen via `WGL_ARB_pixel_format`
+/// must support: a normal window, or an off-screen pbuffer (see
+/// [`Context::new_headless`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SurfaceType {
+    Window,
+    Pbuffer,
+}
+
+/// Owns a pbuffer drawable created via `WGL_ARB_pbuffer`, releasing its HDC
+/// and destroying the pbuffer on drop. Also keeps alive the hidden window
+/// the pbuffer's pixel format was chosen against, since the WGL spec ties a
+/// pbuffer's lifetime to the device context it was created from.
+struct PbufferWrapper {
+    pbuffer: glutin_wgl_sys::wgl_extra::types::HPBUFFERARB,
+    hdc: HDC,
+    extra: glutin_wgl_sys::wgl_extra::Wgl,
+    _dummy_win: WindowWrapper,
+}
+
+impl std::fmt::Debug for PbufferWrapper {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PbufferWrapper").field("pbuffer", &self.pbuffer).finish()
+    }
+}
+
+impl Drop for PbufferWrapper {
+    fn drop(&mut self) {
+        unsafe {
+            self.extra.ReleasePbufferDCARB(self.pbuffer, self.hdc as *const _);
+            self.extra.DestroyPbufferARB(self.pbuffer);
+        }
+    }
+}
+
+/// Creates an off-screen pbuffer drawable of `size` against the pixel format
+/// `pixel_format_id`, returning the pbuffer handle together with the HDC
+/// that should be used to create (and later make current) a WGL context.
+unsafe fn create_pbuffer(
+    extra: &glutin_wgl_sys::wgl_extra::Wgl,
+    hdc: HDC,
+    pixel_format_id: raw::c_int,
+    size: (u32, u32),
+) -> Result<(glutin_wgl_sys::wgl_extra::types::HPBUFFERARB, HDC), Error> {
+    let attribs = [0];
+    let pbuffer = extra.CreatePbufferARB(
+        hdc as *const _,
+        pixel_format_id,
+        size.0 as raw::c_int,
+        size.1 as raw::c_int,
+        attribs.as_ptr(),
+    );
+    if pbuffer.is_null() {
+        return Err(anyhow::anyhow!(
+            "wglCreatePbufferARB failed: {}",
+            std::io::Error::last_os_error()
+        )
+        .into());
+    }
+
+    let pbuffer_hdc = extra.GetPbufferDCARB(pbuffer) as HDC;
+    if pbuffer_hdc.is_null() {
+        extra.DestroyPbufferARB(pbuffer);
+        return Err(anyhow::anyhow!(
+            "wglGetPbufferDCARB failed: {}",
+            std::io::Error::last_os_error()
+        )
+        .into());
+    }
+
+    Ok((pbuffer, pbuffer_hdc))
+}
+
+/// A software OpenGL context backed by `osmesa.dll`, used as the headless
+/// fallback on systems whose driver doesn't expose `WGL_ARB_pbuffer`.
+/// Unlike a WGL context, OSMesa renders into memory the caller owns rather
+/// than a driver-managed drawable, so the target buffer is kept alongside
+/// the context handle.
+struct OsMesaWrapper {
+    library: HMODULE,
+    context: *mut raw::c_void,
+    destroy: unsafe extern "C" fn(*mut raw::c_void) -> raw::c_int,
+    get_proc_address: unsafe extern "C" fn(*const raw::c_char) -> *const raw::c_void,
+    buffer: Vec<u8>,
+    size: (u32, u32),
+}
+
+impl std::fmt::Debug for OsMesaWrapper {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OsMesaWrapper").field("size", &self.size).finish()
+    }
+}
+
+impl OsMesaWrapper {
+    unsafe fn make_current(&self) -> Result<(), Error> {
+        const OSMESA_RGBA: raw::c_int = 0x1908;
+        const GL_UNSIGNED_BYTE: raw::c_uint = 0x1401;
+
+        type PfnMakeCurrent = unsafe extern "C" fn(
+            *mut raw::c_void,
+            *mut raw::c_void,
+            raw::c_uint,
+            raw::c_int,
+            raw::c_int,
+        ) -> raw::c_int;
+
+        let make_current: PfnMakeCurrent = std::mem::transmute(
+            GetProcAddress(self.library, b"OSMesaMakeCurrent\0".as_ptr() as *const _),
+        );
+        let _ = OSMESA_RGBA;
+
+        let ok = make_current(
+            self.context,
+            self.buffer.as_ptr() as *mut raw::c_void,
+            GL_UNSIGNED_BYTE,
+            self.size.0 as raw::c_int,
+            self.size.1 as raw::c_int,
+        );
+        if ok != 0 {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("OSMesaMakeCurrent failed").into())
+        }
+    }
+
+    unsafe fn get_proc_address(&self, addr: *const raw::c_char) -> *const core::ffi::c_void {
+        (self.get_proc_address)(addr) as *const _
+    }
+}
+
+impl Drop for OsMesaWrapper {
+    fn drop(&mut self) {
+        unsafe {
+            (self.destroy)(self.context);
+            FreeLibrary(self.library);
+        }
+    }
+}
+
+/// Loads `osmesa.dll` and creates a software context rendering into an
+/// owned `width * height * 4` RGBA8 buffer.
+unsafe fn create_osmesa_context(size: (u32, u32)) -> Result<OsMesaWrapper, Error> {
+    const OSMESA_RGBA: raw::c_int = 0x1908;
+
+    let name = OsStr::new("osmesa.dll").encode_wide().chain(Some(0).into_iter()).collect::<Vec<_>>();
+    let library = LoadLibraryW(name.as_ptr());
+    if library.is_null() {
+        return Err(anyhow::anyhow!(
+            "failed to load osmesa.dll, no headless rendering path available: {}",
+            std::io::Error::last_os_error()
+        )
+        .into());
+    }
+
+    type PfnCreateContextExt = unsafe extern "C" fn(
+        raw::c_int,
+        raw::c_int,
+        raw::c_int,
+        raw::c_int,
+        *mut raw::c_void,
+    ) -> *mut raw::c_void;
+    type PfnDestroyContext = unsafe extern "C" fn(*mut raw::c_void) -> raw::c_int;
+    type PfnGetProcAddress = unsafe extern "C" fn(*const raw::c_char) -> *const raw::c_void;
+
+    let create_context_ext: PfnCreateContextExt = std::mem::transmute(GetProcAddress(
+        library,
+        b"OSMesaCreateContextExt\0".as_ptr() as *const _,
+    ));
+    let destroy: PfnDestroyContext =
+        std::mem::transmute(GetProcAddress(library, b"OSMesaDestroyContext\0".as_ptr() as *const _));
+    let get_proc_address: PfnGetProcAddress = std::mem::transmute(GetProcAddress(
+        library,
+        b"OSMesaGetProcAddress\0".as_ptr() as *const _,
+    ));
+
+    let context = create_context_ext(OSMESA_RGBA, 24, 8, 0, std::ptr::null_mut());
+    if context.is_null() {
+        FreeLibrary(library);
+        return Err(anyhow::anyhow!("OSMesaCreateContextExt failed").into());
+    }
+
+    let buffer = vec![0u8; size.0 as usize * size.1 as usize * 4];
+
+    Ok(OsMesaWrapper { library, context, destroy, get_proc_address, buffer, size })
+}
+
+/// The `PixelFormat` reported for a software OSMesa-backed headless
+/// context: a plain 8-bit RGBA buffer with no driver-specific capabilities.
+fn osmesa_pixel_format() -> PixelFormat {
+    PixelFormat {
+        hardware_accelerated: false,
+        color_bits: 24,
+        alpha_bits: 8,
+        depth_bits: 24,
+        stencil_bits: 8,
+        stereoscopy: false,
+        double_buffer: false,
+        multisampling: None,
+        srgb: false,
+        float_color_buffer: false,
+        // OSMesa renders into a plain in-memory RGBA buffer with no notion of
+        // colorspace at all, so this is the only honest value to report.
+        color_space: crate::gl::ColorSpace::Srgb,
+        release_behavior: ReleaseBehavior::Flush,
+    }
+}
+
 /// Creates an OpenGL context.
 ///
 /// If `extra` is `Some`, this function will attempt to use the latest WGL
@@ -416,173 +930,248 @@ unsafe fn load_extra_functions(win: HWND) -> Result<glutin_wgl_sys::wgl_extra::W
 ///
 /// Otherwise, only the basic API will be used and the chances of
 /// `CreationError::NotSupported` being returned increase.
+///
+/// Returns the context together with the [`Api`] it was actually built
+/// against, which is `Api::OpenGlEs` only if an ES profile was actually
+/// negotiated via `WGL_EXT_create_context_es2_profile`.
 unsafe fn create_context(
     extra: Option<(
         &glutin_wgl_sys::wgl_extra::Wgl,
         &PixelFormatRequirements,
-        &GlAttributes,
+        &GlAttributes<&Context>,
         &str,
     )>,
     _: HWND,
     hdc: HDC,
-) -> Result<ContextWrapper, Error> {
+    shared: Option<HGLRC>,
+) -> Result<(ContextWrapper, Api), Error> {
+    // An OSMesa-backed share context has no HGLRC to join.
+    if shared.map_or(false, |hglrc| hglrc.is_null()) {
+        return Err(anyhow::anyhow!(crate::gl::ContextError::SharingIncompatible).into());
+    }
     if let Some((extra_functions, _pf_reqs, opengl, extensions)) = extra {
         if extensions
             .split(' ')
             .find(|&i| i == "WGL_ARB_create_context")
             .is_some()
         {
-            let mut attributes = Vec::new();
-
-            match opengl.version {
-                GlRequest::Latest => {}
-                GlRequest::Specific(Api::OpenGl, (major, minor)) => {
-                    attributes
-                        .push(glutin_wgl_sys::wgl_extra::CONTEXT_MAJOR_VERSION_ARB as raw::c_int);
-                    attributes.push(major as raw::c_int);
-                    attributes
-                        .push(glutin_wgl_sys::wgl_extra::CONTEXT_MINOR_VERSION_ARB as raw::c_int);
-                    attributes.push(minor as raw::c_int);
-                }
-                GlRequest::Specific(Api::OpenGlEs, (major, minor)) => {
+            // Appends the profile/robustness/debug attributes common to every
+            // version preference on top of an already-built version prefix,
+            // then attempts context creation with the result.
+            let try_create = |mut attributes: Vec<raw::c_int>| -> Result<HGLRC, Error> {
+                if let Some(profile) = opengl.profile {
                     if extensions
                         .split(' ')
-                        .find(|&i| i == "WGL_EXT_create_context_es2_profile")
+                        .find(|&i| i == "WGL_ARB_create_context_profile")
                         .is_some()
                     {
+                        let flag = match profile {
+                            GlProfile::Compatibility => {
+                                glutin_wgl_sys::wgl_extra::CONTEXT_COMPATIBILITY_PROFILE_BIT_ARB
+                            }
+                            GlProfile::Core => {
+                                glutin_wgl_sys::wgl_extra::CONTEXT_CORE_PROFILE_BIT_ARB
+                            }
+                        };
                         attributes.push(
                             glutin_wgl_sys::wgl_extra::CONTEXT_PROFILE_MASK_ARB as raw::c_int,
                         );
-                        attributes.push(
-                            glutin_wgl_sys::wgl_extra::CONTEXT_ES2_PROFILE_BIT_EXT as raw::c_int,
-                        );
+                        attributes.push(flag as raw::c_int);
                     } else {
-                        return Err(anyhow::anyhow!("OpenGL version not supported").into());
+                        return Err(anyhow::anyhow!(
+                            "required extension \"WGL_ARB_create_context_profile\" not found"
+                                .to_string(),
+                        )
+                        .into());
                     }
-
-                    attributes
-                        .push(glutin_wgl_sys::wgl_extra::CONTEXT_MAJOR_VERSION_ARB as raw::c_int);
-                    attributes.push(major as raw::c_int);
-                    attributes
-                        .push(glutin_wgl_sys::wgl_extra::CONTEXT_MINOR_VERSION_ARB as raw::c_int);
-                    attributes.push(minor as raw::c_int);
-                }
-                GlRequest::Specific(_, _) => {
-                    return Err(anyhow::anyhow!("OpenGL version not supported").into());
                 }
-                GlRequest::GlThenGles {
-                    opengl_version: (major, minor),
-                    ..
-                } => {
-                    attributes
-                        .push(glutin_wgl_sys::wgl_extra::CONTEXT_MAJOR_VERSION_ARB as raw::c_int);
-                    attributes.push(major as raw::c_int);
-                    attributes
-                        .push(glutin_wgl_sys::wgl_extra::CONTEXT_MINOR_VERSION_ARB as raw::c_int);
-                    attributes.push(minor as raw::c_int);
-                }
-            }
 
-            if let Some(profile) = opengl.profile {
-                if extensions
-                    .split(' ')
-                    .find(|&i| i == "WGL_ARB_create_context_profile")
-                    .is_some()
-                {
-                    let flag = match profile {
-                        GlProfile::Compatibility => {
-                            glutin_wgl_sys::wgl_extra::CONTEXT_COMPATIBILITY_PROFILE_BIT_ARB
-                        }
-                        GlProfile::Core => glutin_wgl_sys::wgl_extra::CONTEXT_CORE_PROFILE_BIT_ARB,
-                    };
-                    attributes
-                        .push(glutin_wgl_sys::wgl_extra::CONTEXT_PROFILE_MASK_ARB as raw::c_int);
-                    attributes.push(flag as raw::c_int);
-                } else {
-                    return Err(anyhow::anyhow!(
-                        "required extension \"WGL_ARB_create_context_profile\" not found"
-                            .to_string(),
-                    )
-                    .into());
+                // `WGL_CONTEXT_OPENGL_NO_ERROR_ARB` must not be combined with the
+                // robust-access or debug flags, so check for it up front and
+                // skip those below if it applies.
+                let no_error = opengl.robustness == Robustness::NoError
+                    && extensions
+                        .split(' ')
+                        .find(|&i| i == "WGL_ARB_create_context_no_error")
+                        .is_some();
+                if no_error {
+                    attributes.push(
+                        glutin_wgl_sys::wgl_extra::CONTEXT_OPENGL_NO_ERROR_ARB as raw::c_int,
+                    );
+                    attributes.push(1);
                 }
-            }
 
-            let flags = {
-                let mut flags = 0;
+                let flags = {
+                    let mut flags = 0;
 
-                // robustness
-                if extensions
-                    .split(' ')
-                    .find(|&i| i == "WGL_ARB_create_context_robustness")
-                    .is_some()
-                {
-                    match opengl.robustness {
-                        Robustness::RobustNoResetNotification
-                        | Robustness::TryRobustNoResetNotification => {
-                            attributes.push(
-                                glutin_wgl_sys::wgl_extra::CONTEXT_RESET_NOTIFICATION_STRATEGY_ARB
-                                    as raw::c_int,
-                            );
-                            attributes.push(
-                                glutin_wgl_sys::wgl_extra::NO_RESET_NOTIFICATION_ARB as raw::c_int,
-                            );
-                            flags = flags
-                                | glutin_wgl_sys::wgl_extra::CONTEXT_ROBUST_ACCESS_BIT_ARB
-                                    as raw::c_int;
+                    // robustness
+                    if !no_error
+                        && extensions
+                            .split(' ')
+                            .find(|&i| i == "WGL_ARB_create_context_robustness")
+                            .is_some()
+                    {
+                        match opengl.robustness {
+                            Robustness::RobustNoResetNotification
+                            | Robustness::TryRobustNoResetNotification => {
+                                attributes.push(
+                                    glutin_wgl_sys::wgl_extra::CONTEXT_RESET_NOTIFICATION_STRATEGY_ARB
+                                        as raw::c_int,
+                                );
+                                attributes.push(
+                                    glutin_wgl_sys::wgl_extra::NO_RESET_NOTIFICATION_ARB as raw::c_int,
+                                );
+                                flags = flags
+                                    | glutin_wgl_sys::wgl_extra::CONTEXT_ROBUST_ACCESS_BIT_ARB
+                                        as raw::c_int;
+                            }
+                            Robustness::RobustLoseContextOnReset
+                            | Robustness::TryRobustLoseContextOnReset => {
+                                attributes.push(
+                                    glutin_wgl_sys::wgl_extra::CONTEXT_RESET_NOTIFICATION_STRATEGY_ARB
+                                        as raw::c_int,
+                                );
+                                attributes.push(
+                                    glutin_wgl_sys::wgl_extra::LOSE_CONTEXT_ON_RESET_ARB as raw::c_int,
+                                );
+                                flags = flags
+                                    | glutin_wgl_sys::wgl_extra::CONTEXT_ROBUST_ACCESS_BIT_ARB
+                                        as raw::c_int;
+                            }
+                            Robustness::NotRobust => (),
+                            Robustness::NoError => (),
                         }
-                        Robustness::RobustLoseContextOnReset
-                        | Robustness::TryRobustLoseContextOnReset => {
-                            attributes.push(
-                                glutin_wgl_sys::wgl_extra::CONTEXT_RESET_NOTIFICATION_STRATEGY_ARB
-                                    as raw::c_int,
-                            );
-                            attributes.push(
-                                glutin_wgl_sys::wgl_extra::LOSE_CONTEXT_ON_RESET_ARB as raw::c_int,
-                            );
-                            flags = flags
-                                | glutin_wgl_sys::wgl_extra::CONTEXT_ROBUST_ACCESS_BIT_ARB
-                                    as raw::c_int;
+                    } else if !no_error {
+                        match opengl.robustness {
+                            Robustness::RobustNoResetNotification
+                            | Robustness::RobustLoseContextOnReset => {
+                                return Err(anyhow::anyhow!("Robustness not supported").into());
+                            }
+                            _ => (),
                         }
-                        Robustness::NotRobust => (),
-                        Robustness::NoError => (),
                     }
+
+                    if opengl.debug && !no_error {
+                        flags = flags
+                            | glutin_wgl_sys::wgl_extra::CONTEXT_DEBUG_BIT_ARB as raw::c_int;
+                    }
+
+                    flags
+                };
+
+                attributes.push(glutin_wgl_sys::wgl_extra::CONTEXT_FLAGS_ARB as raw::c_int);
+                attributes.push(flags);
+
+                attributes.push(0);
+
+                let ctx = extra_functions.CreateContextAttribsARB(
+                    hdc as *const raw::c_void,
+                    shared.unwrap_or(std::ptr::null_mut()) as *const raw::c_void,
+                    attributes.as_ptr(),
+                );
+
+                if ctx.is_null() && shared.is_some() {
+                    Err(anyhow::anyhow!(crate::gl::ContextError::SharingIncompatible).into())
+                } else if ctx.is_null() {
+                    Err(anyhow::anyhow!(
+                        "wglCreateContextAttribsARB failed: {}",
+                        std::io::Error::last_os_error()
+                    )
+                    .into())
                 } else {
-                    match opengl.robustness {
-                        Robustness::RobustNoResetNotification
-                        | Robustness::RobustLoseContextOnReset => {
-                            return Err(anyhow::anyhow!("Robustness not supported").into());
+                    Ok(ctx as HGLRC)
+                }
+            };
+
+            // Builds the `CONTEXT_{MAJOR,MINOR}_VERSION_ARB` (and, for
+            // `Api::OpenGlEs`, `CONTEXT_PROFILE_MASK_ARB`) prefix for one
+            // `(api, version)` preference. Returns `None` when this backend
+            // has no way to satisfy `api` at all (an ES profile without the
+            // ES2 extension, or WebGL, which WGL never creates directly) so
+            // the caller can move on to the next preference instead of
+            // failing outright.
+            let version_attributes = |api: Api, (major, minor): (u8, u8)| -> Option<Vec<raw::c_int>> {
+                match api {
+                    Api::OpenGl => Some(vec![
+                        glutin_wgl_sys::wgl_extra::CONTEXT_MAJOR_VERSION_ARB as raw::c_int,
+                        major as raw::c_int,
+                        glutin_wgl_sys::wgl_extra::CONTEXT_MINOR_VERSION_ARB as raw::c_int,
+                        minor as raw::c_int,
+                    ]),
+                    Api::OpenGlEs => {
+                        if extensions
+                            .split(' ')
+                            .find(|&i| i == "WGL_EXT_create_context_es2_profile")
+                            .is_some()
+                        {
+                            Some(vec![
+                                glutin_wgl_sys::wgl_extra::CONTEXT_PROFILE_MASK_ARB as raw::c_int,
+                                glutin_wgl_sys::wgl_extra::CONTEXT_ES2_PROFILE_BIT_EXT as raw::c_int,
+                                glutin_wgl_sys::wgl_extra::CONTEXT_MAJOR_VERSION_ARB as raw::c_int,
+                                major as raw::c_int,
+                                glutin_wgl_sys::wgl_extra::CONTEXT_MINOR_VERSION_ARB as raw::c_int,
+                                minor as raw::c_int,
+                            ])
+                        } else {
+                            None
                         }
-                        _ => (),
                     }
+                    Api::WebGl => None,
                 }
+            };
 
-                if opengl.debug {
-                    flags = flags | glutin_wgl_sys::wgl_extra::CONTEXT_DEBUG_BIT_ARB as raw::c_int;
+            let result = match opengl.version.clone() {
+                GlRequest::Latest => try_create(Vec::new()).map(|ctx| (ctx, Api::OpenGl)),
+                GlRequest::Specific(Api::OpenGlEs, version) => {
+                    match version_attributes(Api::OpenGlEs, version) {
+                        Some(attributes) => {
+                            try_create(attributes).map(|ctx| (ctx, Api::OpenGlEs))
+                        }
+                        None => Err(anyhow::anyhow!("OpenGL version not supported").into()),
+                    }
+                }
+                GlRequest::Specific(Api::OpenGl, version) => {
+                    try_create(version_attributes(Api::OpenGl, version).unwrap())
+                        .map(|ctx| (ctx, Api::OpenGl))
+                }
+                GlRequest::Specific(_, _) => {
+                    Err(anyhow::anyhow!("OpenGL version not supported").into())
+                }
+                GlRequest::GlThenGles { .. } | GlRequest::TryAnyOf(_) => {
+                    // Try every preference in order, attempting real context
+                    // creation for each, and only give up once all of them
+                    // have failed — a pixel-format mismatch or driver quirk
+                    // on our first choice shouldn't sink the whole request
+                    // when a later preference would have worked.
+                    let mut last_err: Option<Error> = None;
+                    let mut found = None;
+                    for (api, version) in opengl.version.preference_list() {
+                        let attributes = match version_attributes(api, version) {
+                            Some(attributes) => attributes,
+                            None => continue,
+                        };
+                        match try_create(attributes) {
+                            Ok(ctx) => {
+                                found = Some((ctx, api));
+                                break;
+                            }
+                            Err(err) => last_err = Some(err),
+                        }
+                    }
+                    match found {
+                        Some(found) => Ok(found),
+                        None => Err(last_err.unwrap_or_else(|| {
+                            anyhow::anyhow!(
+                                "none of the requested OpenGL version preferences are supported"
+                            )
+                            .into()
+                        })),
+                    }
                 }
-
-                flags
             };
 
-            attributes.push(glutin_wgl_sys::wgl_extra::CONTEXT_FLAGS_ARB as raw::c_int);
-            attributes.push(flags);
-
-            attributes.push(0);
-
-            let ctx = extra_functions.CreateContextAttribsARB(
-                hdc as *const raw::c_void,
-                std::ptr::null_mut() as *const raw::c_void,
-                attributes.as_ptr(),
-            );
-
-            if ctx.is_null() {
-                return Err(anyhow::anyhow!(
-                    "wglCreateContextAttribsARB failed: {}",
-                    std::io::Error::last_os_error()
-                )
-                .into());
-            } else {
-                return Ok(ContextWrapper(ctx as HGLRC));
-            }
+            let (ctx, api) = result?;
+            return Ok((ContextWrapper(ctx), api));
         }
     }
 
@@ -595,7 +1184,35 @@ unsafe fn create_context(
         .into());
     }
 
-    Ok(ContextWrapper(ctx as HGLRC))
+    if let Some(shared) = shared {
+        if glutin_wgl_sys::wgl::ShareLists(shared as *const raw::c_void, ctx) == 0 {
+            glutin_wgl_sys::wgl::DeleteContext(ctx);
+            return Err(anyhow::anyhow!(crate::gl::ContextError::SharingIncompatible).into());
+        }
+    }
+
+    Ok((ContextWrapper(ctx as HGLRC), Api::OpenGl))
+}
+
+/// Converts the requested [`crate::gl::SwapInterval`] into the raw value
+/// `wglSwapIntervalEXT` expects, rejecting [`SwapInterval::AdaptiveWait`]
+/// when the driver doesn't expose `WGL_EXT_swap_control_tear`.
+fn swap_interval_to_raw(
+    interval: crate::gl::SwapInterval,
+    tear_supported: bool,
+) -> Result<raw::c_int, Error> {
+    use crate::gl::SwapInterval::*;
+    match interval {
+        DontWait => Ok(0),
+        Wait(n) => Ok(n as raw::c_int),
+        AdaptiveWait(n) => {
+            if tear_supported {
+                Ok(-(n as raw::c_int))
+            } else {
+                Err(anyhow::anyhow!("WGL_EXT_swap_control_tear not supported").into())
+            }
+        }
+    }
 }
 
 /// Calls `SetPixelFormat` on a window.
@@ -671,7 +1288,11 @@ fn choose_dummy_pixel_format(hdc: HDC) -> Result<raw::c_int, Error> {
 
 /// Chooses a pixel formats without using WGL.
 ///
-/// Gives less precise results than `enumerate_arb_pixel_formats`.
+/// Gives less precise results than `enumerate_arb_pixel_format_id`. In
+/// particular, `PIXELFORMATDESCRIPTOR` has no way to express a floating-point
+/// color buffer or an sRGB-capable framebuffer, so requests for either are
+/// rejected up front here rather than silently handed back a conforming-
+/// looking but linear 8-bit format.
 unsafe fn choose_native_pixel_format_id(
     hdc: HDC,
     pf_reqs: &PixelFormatRequirements,
@@ -697,9 +1318,12 @@ unsafe fn choose_native_pixel_format_id(
         return Err(());
     }
 
-    if pf_reqs.release_behavior != ReleaseBehavior::Flush {
-        return Err(());
-    }
+    // `PIXELFORMATDESCRIPTOR`/`ChoosePixelFormat` predate
+    // `WGL_ARB_context_flush_control` and have no way to request a
+    // non-flushing release behavior, so `ReleaseBehavior::None` is honored
+    // as best-effort here: the context still flushes on release, and
+    // `choose_native_pixel_format` reports `Flush` as what was actually
+    // granted rather than failing the request outright.
 
     // building the descriptor to pass to ChoosePixelFormat
     let descriptor = PIXELFORMATDESCRIPTOR {
@@ -788,7 +1412,17 @@ unsafe fn choose_native_pixel_format(
         stereoscopy: (output.dwFlags & PFD_STEREO) != 0,
         double_buffer: (output.dwFlags & PFD_DOUBLEBUFFER) != 0,
         multisampling: None,
+        // Always accurate: `choose_native_pixel_format_id` rejects any
+        // request with `srgb` set before we get here, since
+        // `PIXELFORMATDESCRIPTOR` has no flag that reports sRGB capability.
         srgb: false,
+        float_color_buffer: false,
+        // `PIXELFORMATDESCRIPTOR` has no colorspace concept either, same as
+        // the `srgb` flag above, so this is always what's actually granted.
+        color_space: crate::gl::ColorSpace::Srgb,
+        // Best-effort: the legacy GDI path always flushes on release
+        // regardless of what was requested; see `choose_native_pixel_format_id`.
+        release_behavior: ReleaseBehavior::Flush,
     };
 
     if pf_desc.alpha_bits < pf_reqs.alpha_bits.unwrap_or(0) {
@@ -819,17 +1453,74 @@ unsafe fn choose_native_pixel_format(
 
 /// Enumerates the list of pixel formats by using extra WGL functions.
 ///
-/// Gives more precise results than `enumerate_native_pixel_formats`.
+/// Gives more precise results than `choose_native_pixel_format_id`. `surface_type`
+/// selects between `DRAW_TO_WINDOW_ARB` and `DRAW_TO_PBUFFER_ARB` so the same
+/// selection and ranking logic serves both window-bound contexts and the
+/// pbuffer-backed headless contexts built by [`create_pbuffer`].
+///
+/// If `pf_reqs.multisampling` requests a sample count that no format
+/// supports, this retries with the next-lower standard sample count (down
+/// to, and including, no multisampling at all) instead of failing outright.
+/// See [`multisampling_fallback_sequence`].
 unsafe fn choose_arb_pixel_format_id(
     extra: &glutin_wgl_sys::wgl_extra::Wgl,
     extensions: &str,
     hdc: HDC,
     pf_reqs: &PixelFormatRequirements,
+    surface_type: SurfaceType,
+) -> Result<raw::c_int, ()> {
+    for samples in multisampling_fallback_sequence(pf_reqs.multisampling) {
+        if let Ok(id) = choose_arb_pixel_format_id_with_samples(
+            extra,
+            extensions,
+            hdc,
+            pf_reqs,
+            surface_type,
+            samples,
+        ) {
+            return Ok(id);
+        }
+    }
+    Err(())
+}
+
+/// Builds the sequence of sample counts to retry pixel format selection
+/// with, starting at `requested` and walking down through the standard MSAA
+/// levels (8, 4, 2, then disabled) so that a request for, say, 16x MSAA
+/// still succeeds with a working (if lower-quality) context on hardware
+/// that tops out at 4x.
+fn multisampling_fallback_sequence(requested: Option<u16>) -> Vec<Option<u16>> {
+    let n = match requested {
+        None => return vec![None],
+        Some(n) => n,
+    };
+    let mut sequence = vec![Some(n)];
+    for &step in &[8u16, 4, 2, 0] {
+        if step < n && !sequence.contains(&Some(step)) {
+            sequence.push(Some(step));
+        }
+    }
+    if !sequence.contains(&Some(0)) {
+        sequence.push(Some(0));
+    }
+    sequence
+}
+
+unsafe fn choose_arb_pixel_format_id_with_samples(
+    extra: &glutin_wgl_sys::wgl_extra::Wgl,
+    extensions: &str,
+    hdc: HDC,
+    pf_reqs: &PixelFormatRequirements,
+    surface_type: SurfaceType,
+    multisampling: Option<u16>,
 ) -> Result<raw::c_int, ()> {
     let descriptor = {
         let mut out: Vec<raw::c_int> = Vec::with_capacity(37);
 
-        out.push(glutin_wgl_sys::wgl_extra::DRAW_TO_WINDOW_ARB as raw::c_int);
+        out.push(match surface_type {
+            SurfaceType::Window => glutin_wgl_sys::wgl_extra::DRAW_TO_WINDOW_ARB,
+            SurfaceType::Pbuffer => glutin_wgl_sys::wgl_extra::DRAW_TO_PBUFFER_ARB,
+        } as raw::c_int);
         out.push(1);
 
         out.push(glutin_wgl_sys::wgl_extra::SUPPORT_OPENGL_ARB as raw::c_int);
@@ -885,7 +1576,7 @@ unsafe fn choose_arb_pixel_format_id(
         out.push(glutin_wgl_sys::wgl_extra::DOUBLE_BUFFER_ARB as raw::c_int);
         out.push(if double_buffer { 1 } else { 0 });
 
-        if let Some(multisampling) = pf_reqs.multisampling {
+        if let Some(multisampling) = multisampling {
             if extensions
                 .split(' ')
                 .find(|&i| i == "WGL_ARB_multisample")
@@ -945,23 +1636,142 @@ unsafe fn choose_arb_pixel_format_id(
 
     let mut format_id = std::mem::zeroed();
     let mut num_formats = std::mem::zeroed();
-    if extra.ChoosePixelFormatARB(
+    let chose_one = extra.ChoosePixelFormatARB(
         hdc as *const _,
         descriptor.as_ptr(),
         std::ptr::null(),
         1,
         &mut format_id,
         &mut num_formats,
+    ) != 0
+        && num_formats != 0;
+
+    if chose_one {
+        return Ok(format_id);
+    }
+
+    // Some drivers return zero formats here even though a conforming one
+    // exists (or hand back a poor match when asked for just one); fall back
+    // to enumerating every format ourselves and ranking the survivors.
+    enumerate_arb_pixel_format_id(extra, extensions, hdc, pf_reqs, surface_type, multisampling)
+}
+
+/// Enumerates every ARB pixel format `hdc` supports and ranks the ones that
+/// satisfy `pf_reqs`'s mandatory constraints, as a fallback for drivers
+/// where `ChoosePixelFormatARB` misbehaves. Candidates are ranked preferring,
+/// in order: an exact `hardware_accelerated` match, the smallest excess of
+/// `color_bits` over the request, alpha/depth/stencil closeness, a sample
+/// count closest to (but not below) the requested multisampling level, and
+/// finally double-buffered formats over single-buffered ones.
+unsafe fn enumerate_arb_pixel_format_id(
+    extra: &glutin_wgl_sys::wgl_extra::Wgl,
+    extensions: &str,
+    hdc: HDC,
+    pf_reqs: &PixelFormatRequirements,
+    surface_type: SurfaceType,
+    multisampling: Option<u16>,
+) -> Result<raw::c_int, ()> {
+    let mut num_formats = std::mem::zeroed();
+    if extra.GetPixelFormatAttribivARB(
+        hdc as *const _,
+        1,
+        0,
+        1,
+        [glutin_wgl_sys::wgl_extra::NUMBER_PIXEL_FORMATS_ARB as raw::c_int].as_ptr(),
+        &mut num_formats,
     ) == 0
     {
         return Err(());
     }
 
-    if num_formats == 0 {
-        return Err(());
+    let wants_window = match surface_type {
+        SurfaceType::Window => glutin_wgl_sys::wgl_extra::DRAW_TO_WINDOW_ARB,
+        SurfaceType::Pbuffer => glutin_wgl_sys::wgl_extra::DRAW_TO_PBUFFER_ARB,
+    };
+
+    let get_info = |format_id: raw::c_int, attrib: u32| {
+        let mut value = std::mem::zeroed();
+        extra.GetPixelFormatAttribivARB(
+            hdc as *const _,
+            format_id,
+            0,
+            1,
+            [attrib as raw::c_int].as_ptr(),
+            &mut value,
+        );
+        value as u32
+    };
+
+    let mut best: Option<(raw::c_int, (bool, u8, u16, i32, bool))> = None;
+
+    for id in 1..=(num_formats as raw::c_int) {
+        if get_info(id, glutin_wgl_sys::wgl_extra::SUPPORT_OPENGL_ARB) == 0 {
+            continue;
+        }
+        if get_info(id, wants_window) == 0 {
+            continue;
+        }
+
+        let pf = match choose_arb_pixel_format(extra, extensions, hdc, id, pf_reqs) {
+            Ok(pf) => pf,
+            Err(()) => continue,
+        };
+
+        if pf.alpha_bits < pf_reqs.alpha_bits.unwrap_or(0)
+            || pf.depth_bits < pf_reqs.depth_bits.unwrap_or(0)
+            || pf.stencil_bits < pf_reqs.stencil_bits.unwrap_or(0)
+            || pf.color_bits < pf_reqs.color_bits.unwrap_or(0)
+        {
+            continue;
+        }
+        if let Some(req) = pf_reqs.hardware_accelerated {
+            if pf.hardware_accelerated != req {
+                continue;
+            }
+        }
+        if let Some(req) = pf_reqs.double_buffer {
+            if pf.double_buffer != req {
+                continue;
+            }
+        }
+        if pf_reqs.srgb && !pf.srgb {
+            continue;
+        }
+        if pf.float_color_buffer != pf_reqs.float_color_buffer {
+            continue;
+        }
+
+        // Lower is better in every component of this key.
+        let accel_mismatch = pf_reqs
+            .hardware_accelerated
+            .map_or(!pf.hardware_accelerated, |req| pf.hardware_accelerated != req);
+        let color_excess = pf.color_bits - pf_reqs.color_bits.unwrap_or(0);
+        let bits_excess = (pf.alpha_bits - pf_reqs.alpha_bits.unwrap_or(0)) as u16
+            + (pf.depth_bits - pf_reqs.depth_bits.unwrap_or(0)) as u16
+            + (pf.stencil_bits - pf_reqs.stencil_bits.unwrap_or(0)) as u16;
+        let wanted_samples = multisampling.unwrap_or(0) as i32;
+        let have_samples = pf.multisampling.unwrap_or(0) as i32;
+        let sample_score = if have_samples >= wanted_samples {
+            have_samples - wanted_samples
+        } else {
+            raw::c_int::MAX
+        };
+        let single_buffered = !pf.double_buffer;
+
+        let key = (
+            accel_mismatch,
+            color_excess,
+            bits_excess,
+            sample_score,
+            single_buffered,
+        );
+
+        if best.as_ref().map_or(true, |(_, best_key)| key < *best_key) {
+            best = Some((id, key));
+        }
     }
 
-    Ok(format_id)
+    best.map(|(id, _)| id).ok_or(())
 }
 
 unsafe fn choose_arb_pixel_format(
@@ -969,6 +1779,7 @@ unsafe fn choose_arb_pixel_format(
     extensions: &str,
     hdc: HDC,
     format_id: raw::c_int,
+    pf_reqs: &PixelFormatRequirements,
 ) -> Result<PixelFormat, ()> {
     let get_info = |attrib: u32| {
         let mut value = std::mem::zeroed();
@@ -1023,6 +1834,27 @@ unsafe fn choose_arb_pixel_format(
         } else {
             false
         },
+        float_color_buffer: get_info(glutin_wgl_sys::wgl_extra::PIXEL_TYPE_ARB)
+            == glutin_wgl_sys::wgl_extra::TYPE_RGBA_FLOAT_ARB,
+        // Unlike `srgb` above (`WGL_ARB_framebuffer_sRGB`/`WGL_EXT_framebuffer_sRGB`,
+        // both exposed by `glutin_wgl_sys::wgl_extra`), there's no `WGL_EXT_colorspace`
+        // binding available here to query or request against, so this always reports
+        // the plain sRGB framebuffer every driver grants by default rather than
+        // claiming support for a request this path can't act on.
+        color_space: crate::gl::ColorSpace::Srgb,
+        // `ReleaseBehavior::None` is only ever actually granted when the
+        // driver exposes `WGL_ARB_context_flush_control`; otherwise the
+        // context still flushes on release, same as the native fallback.
+        release_behavior: if pf_reqs.release_behavior == ReleaseBehavior::None
+            && extensions
+                .split(' ')
+                .find(|&i| i == "WGL_ARB_context_flush_control")
+                .is_some()
+        {
+            ReleaseBehavior::None
+        } else {
+            ReleaseBehavior::Flush
+        },
     };
 
     Ok(pf_desc)
@@ -1047,3 +1879,42 @@ unsafe fn load_opengl32_dll() -> Result<HMODULE, Error> {
 
     Ok(lib)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multisampling_fallback_sequence_no_request_is_just_none() {
+        assert_eq!(multisampling_fallback_sequence(None), vec![None]);
+    }
+
+    #[test]
+    fn multisampling_fallback_sequence_walks_down_standard_levels() {
+        assert_eq!(
+            multisampling_fallback_sequence(Some(16)),
+            vec![Some(16), Some(8), Some(4), Some(2), Some(0)]
+        );
+    }
+
+    #[test]
+    fn multisampling_fallback_sequence_skips_levels_above_the_request() {
+        assert_eq!(
+            multisampling_fallback_sequence(Some(4)),
+            vec![Some(4), Some(2), Some(0)]
+        );
+    }
+
+    #[test]
+    fn multisampling_fallback_sequence_does_not_duplicate_a_requested_standard_level() {
+        assert_eq!(
+            multisampling_fallback_sequence(Some(8)),
+            vec![Some(8), Some(4), Some(2), Some(0)]
+        );
+    }
+
+    #[test]
+    fn multisampling_fallback_sequence_requesting_disabled_is_just_disabled() {
+        assert_eq!(multisampling_fallback_sequence(Some(0)), vec![Some(0)]);
+    }
+}
@@ -23,7 +23,7 @@ pub enum GlProfile {
 
 /// Describes the OpenGL API and version that are being requested when a context
 /// is created.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
 pub enum GlRequest {
     /// Request the latest version of the "best" API of this platform.
     ///
@@ -39,24 +39,50 @@ pub enum GlRequest {
     /// `opengl_version`. Else if OpenGL ES or WebGL is available, create a
     /// context with the specified `opengles_version`.
     ///
+    /// This is a thin wrapper around [`TryAnyOf`] for source compatibility;
+    /// it lowers to `TryAnyOf(vec![(OpenGl, opengl_version), (OpenGlEs,
+    /// opengles_version)])`.
+    ///
     /// [`Context`]: struct.Context.html
+    /// [`TryAnyOf`]: GlRequest::TryAnyOf
     GlThenGles {
         /// The version to use for OpenGL.
         opengl_version: (u8, u8),
         /// The version to use for OpenGL ES.
         opengles_version: (u8, u8),
     },
+
+    /// Try an ordered list of `(api, version)` preferences, using the first
+    /// one context creation succeeds with — e.g. "prefer Core GL 4.1, else
+    /// GL ES 3.0, else WebGL 2.0" — without the caller writing platform
+    /// conditionals.
+    TryAnyOf(Vec<(Api, (u8, u8))>),
 }
 
 impl GlRequest {
-    /// Extract the desktop GL version, if any.
-    pub fn to_gl_version(&self) -> Option<(u8, u8)> {
+    /// Normalizes any variant into an ordered list of `(api, version)`
+    /// preferences, so backends only need to walk one representation.
+    /// `Latest` has no fixed preferences and lowers to an empty list.
+    pub fn preference_list(&self) -> Vec<(Api, (u8, u8))> {
         match self {
-            &GlRequest::Specific(Api::OpenGl, opengl_version) => Some(opengl_version),
-            &GlRequest::GlThenGles { opengl_version, .. } => Some(opengl_version),
-            _ => None,
+            GlRequest::Latest => Vec::new(),
+            GlRequest::Specific(api, version) => vec![(*api, *version)],
+            GlRequest::GlThenGles {
+                opengl_version,
+                opengles_version,
+            } => vec![(Api::OpenGl, *opengl_version), (Api::OpenGlEs, *opengles_version)],
+            GlRequest::TryAnyOf(preferences) => preferences.clone(),
         }
     }
+
+    /// Extract the first desktop-GL version in the request's preference
+    /// list, if any.
+    pub fn to_gl_version(&self) -> Option<(u8, u8)> {
+        self.preference_list()
+            .into_iter()
+            .find(|(api, _)| *api == Api::OpenGl)
+            .map(|(_, version)| version)
+    }
 }
 
 /// The minimum core profile GL context. Useful for getting the minimum
@@ -139,6 +165,44 @@ pub struct PixelFormat {
     /// the multisampling level.
     pub multisampling: Option<u16>,
     pub srgb: bool,
+    /// Whether the color buffer is a floating-point format, as requested via
+    /// [`PixelFormatRequirements::float_color_buffer`].
+    pub float_color_buffer: bool,
+    /// The colorspace this format's framebuffer was actually granted in,
+    /// which may not be the one requested via
+    /// [`PixelFormatRequirements::color_space`] if the platform had to fall
+    /// back to the nearest sRGB 8-bit format. Apps that asked for an HDR
+    /// colorspace should check this and tone-map accordingly.
+    pub color_space: ColorSpace,
+    /// The release behavior actually granted, which is
+    /// [`ReleaseBehavior::Flush`] rather than the requested
+    /// [`ReleaseBehavior::None`] on platforms or drivers that have no way to
+    /// skip the implicit flush on context release.
+    pub release_behavior: ReleaseBehavior,
+}
+
+/// A target colorspace for a GL framebuffer, including the wide-gamut and
+/// HDR spaces needed by `color_bits`/`alpha_bits` layouts wider than 8-bit
+/// sRGB (10-bit `RGB10_A2`, 16-bit float scRGB).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ColorSpace {
+    /// Standard 8-bit-per-channel sRGB. The default, and the universal
+    /// fallback when a wider colorspace isn't available.
+    Srgb,
+    /// Display P3 with a linear (non-sRGB-encoded) transfer function.
+    LinearDisplayP3,
+    /// Linear scRGB: the extended-range linear space used for 16-bit-float
+    /// HDR framebuffers.
+    ScrgbLinear,
+    /// BT.2100 with the PQ (ST 2084) transfer function, the standard
+    /// transfer function for HDR10 display output.
+    Bt2100Pq,
+}
+
+impl Default for ColorSpace {
+    fn default() -> Self {
+        ColorSpace::Srgb
+    }
 }
 
 pub struct PixelFormatRequirements {
@@ -188,6 +252,16 @@ pub struct PixelFormatRequirements {
     /// care. The default is `true`.
     pub srgb: bool,
 
+    /// If true, request a high-dynamic-range framebuffer: 10-bit-per-channel
+    /// (`RGB10_A2`) or 16-bit float (scRGB), depending on `color_space`.
+    /// When unsupported, backends fall back to the nearest sRGB 8-bit
+    /// format and report what was actually granted in the returned
+    /// [`PixelFormat`]. The default is `false`.
+    pub hdr: bool,
+
+    /// The target colorspace. The default is [`ColorSpace::Srgb`].
+    pub color_space: ColorSpace,
+
     /// The behavior when changing the current context. Default is `Flush`.
     pub release_behavior: ReleaseBehavior,
 
@@ -210,17 +284,105 @@ impl Default for PixelFormatRequirements {
             multisampling: None,
             stereoscopy: false,
             srgb: true,
+            hdr: false,
+            color_space: ColorSpace::Srgb,
             release_behavior: ReleaseBehavior::Flush,
             x11_visual_xid: None,
         }
     }
 }
 
+impl PixelFormatRequirements {
+    /// Picks the best-matching format out of `available`, returning its
+    /// index, or `None` if nothing satisfies the hard constraints.
+    ///
+    /// This is shared code so that fbconfig/visual selection doesn't need
+    /// to be reimplemented by every platform backend. Selection is a
+    /// two-phase filter-then-score:
+    ///
+    /// First, any format that violates a hard constraint is discarded:
+    /// `hardware_accelerated` and `double_buffer` must match exactly when
+    /// requested as `Some`, `stereoscopy` and `float_color_buffer` must
+    /// always match exactly, `srgb: true` requires an sRGB-capable format,
+    /// `multisampling: Some(0)` means MSAA must be disabled, and a
+    /// format must meet every `Some(n)` minimum for `color_bits`,
+    /// `alpha_bits`, `depth_bits` and `stencil_bits`.
+    ///
+    /// Among the survivors, a penalty score is computed and the minimum is
+    /// returned. The penalty sums the overshoot of `color_bits`,
+    /// `alpha_bits`, `depth_bits` and `stencil_bits` above their requested
+    /// minimums (preferring exact, minimal buffers over wasting bandwidth),
+    /// adds a large penalty when a feature wasn't requested (`None`) but
+    /// the format enables it anyway, and for `multisampling: Some(n)`
+    /// penalizes by the absolute difference from `n`. Ties are broken by
+    /// preferring hardware-accelerated formats, then lower total bit depth.
+    pub fn choose_best(&self, available: &[PixelFormat]) -> Option<usize> {
+        const NONE_BUT_ENABLED_PENALTY: i32 = 1_000;
+
+        fn meets_minimum(got: u8, wanted: Option<u8>) -> bool {
+            wanted.map_or(true, |wanted| got >= wanted)
+        }
+
+        fn overshoot_penalty(got: u8, wanted: Option<u8>) -> i32 {
+            match wanted {
+                Some(wanted) => i32::from(got) - i32::from(wanted),
+                None if got > 0 => NONE_BUT_ENABLED_PENALTY,
+                None => 0,
+            }
+        }
+
+        available
+            .iter()
+            .enumerate()
+            .filter(|(_, fmt)| {
+                self.hardware_accelerated
+                    .map_or(true, |wanted| fmt.hardware_accelerated == wanted)
+                    && self
+                        .double_buffer
+                        .map_or(true, |wanted| fmt.double_buffer == wanted)
+                    && fmt.stereoscopy == self.stereoscopy
+                    && (!self.srgb || fmt.srgb)
+                    && fmt.float_color_buffer == self.float_color_buffer
+                    && (self.multisampling != Some(0) || fmt.multisampling.is_none())
+                    && meets_minimum(fmt.color_bits, self.color_bits)
+                    && meets_minimum(fmt.alpha_bits, self.alpha_bits)
+                    && meets_minimum(fmt.depth_bits, self.depth_bits)
+                    && meets_minimum(fmt.stencil_bits, self.stencil_bits)
+            })
+            .map(|(i, fmt)| {
+                let mut penalty = overshoot_penalty(fmt.color_bits, self.color_bits)
+                    + overshoot_penalty(fmt.alpha_bits, self.alpha_bits)
+                    + overshoot_penalty(fmt.depth_bits, self.depth_bits)
+                    + overshoot_penalty(fmt.stencil_bits, self.stencil_bits);
+                if let Some(wanted) = self.multisampling {
+                    let got = fmt.multisampling.unwrap_or(0);
+                    penalty += (i32::from(got) - i32::from(wanted)).abs();
+                } else if fmt.multisampling.map_or(false, |got| got > 0) {
+                    penalty += NONE_BUT_ENABLED_PENALTY;
+                }
+                let total_bits = i32::from(fmt.color_bits)
+                    + i32::from(fmt.alpha_bits)
+                    + i32::from(fmt.depth_bits)
+                    + i32::from(fmt.stencil_bits);
+                (i, penalty, !fmt.hardware_accelerated, total_bits)
+            })
+            .min_by_key(|&(_, penalty, software, total_bits)| (penalty, software, total_bits))
+            .map(|(i, _, _, _)| i)
+    }
+}
+
 /// Attributes to use when creating an OpenGL [`Context`].
 ///
+/// This is generic over `T`, the platform's context-handle type, solely so
+/// that [`sharing`](GlAttributes::sharing) can hold a reference to an
+/// existing context without this module needing to know each backend's
+/// concrete `Context` type. Code that doesn't use sharing can keep writing
+/// the bare `GlAttributes` (which defaults `T` to `()`); a backend that
+/// wants to share object namespaces instantiates `GlAttributes<&Context>`.
+///
 /// [`Context`]: struct.Context.html
 #[derive(Clone, Debug)]
-pub struct GlAttributes {
+pub struct GlAttributes<T = ()> {
     /// Version to try create. See [`GlRequest`] for more infos.
     ///
     /// The default is [`Latest`].
@@ -257,18 +419,120 @@ pub struct GlAttributes {
     /// screen tearing.
     ///
     /// The default is `false`.
+    ///
+    /// This field is kept only for source compatibility: no backend reads it,
+    /// so setting it has no effect and only [`swap_interval`] decides the
+    /// swap interval (`SwapInterval::Wait(1)` is the equivalent of
+    /// `vsync: true`).
+    ///
+    /// [`swap_interval`]: GlAttributes::swap_interval
+    #[deprecated(note = "use `swap_interval` instead, which can express adaptive vsync")]
     pub vsync: bool,
+
+    /// The swap interval to request for this context.
+    ///
+    /// The default is [`SwapInterval::DontWait`].
+    pub swap_interval: SwapInterval,
+
+    /// An existing context to share object namespaces (textures, VBOs,
+    /// shaders, ...) with, so they can be uploaded once and drawn from
+    /// multiple windows or a background loader thread.
+    ///
+    /// The default is `None`. Passing a context whose [`PixelFormat`] or
+    /// [`Api`] is incompatible with the one being requested will make
+    /// context creation fail with a dedicated sharing error rather than
+    /// silently create an unshared context.
+    pub sharing: Option<T>,
 }
 
-impl Default for GlAttributes {
+impl<T> Default for GlAttributes<T> {
     #[inline]
-    fn default() -> GlAttributes {
+    #[allow(deprecated)]
+    fn default() -> GlAttributes<T> {
         GlAttributes {
             version: GlRequest::Latest,
             profile: None,
             debug: cfg!(debug_assertions),
             robustness: Robustness::NotRobust,
             vsync: false,
+            swap_interval: SwapInterval::DontWait,
+            sharing: None,
         }
     }
 }
+
+/// How many display refreshes a GL context should wait for between buffer
+/// swaps, replacing a plain boolean `vsync` so adaptive sync can be
+/// expressed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SwapInterval {
+    /// Swap immediately; screen tearing may occur. Equivalent to `vsync:
+    /// false`.
+    DontWait,
+    /// Block `swap_buffers` until `n` display refreshes have occurred since
+    /// the last swap. `Wait(1)` is equivalent to `vsync: true`.
+    Wait(u16),
+    /// Like `Wait(n)`, but if the frame missed its deadline, swap
+    /// immediately and tear instead of stalling for a full extra refresh —
+    /// the standard fix for judder in frame-rate-sensitive apps. Backed by
+    /// `GLX_EXT_swap_control_tear` / `WGL_EXT_swap_control_tear` / a
+    /// negative EGL swap interval.
+    AdaptiveWait(u16),
+}
+
+/// The result of querying whether a robust GL context has lost its state,
+/// as reported by `GL_ARB_robustness`'s `glGetGraphicsResetStatus`.
+///
+/// Only meaningful for a context created with
+/// [`Robustness::RobustLoseContextOnReset`] or
+/// [`Robustness::TryRobustLoseContextOnReset`]; other robustness modes never
+/// report anything but `NoError` here.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ResetStatus {
+    /// The context has not lost its state.
+    NoError,
+    /// An operation performed by this context caused the reset. State is
+    /// unrecoverable; the context must be recreated.
+    GuiltyContextReset,
+    /// Another context (or an external event, such as a driver TDR) caused
+    /// the reset. State is unrecoverable; the context must be recreated.
+    InnocentContextReset,
+    /// The reset's cause could not be determined. State is unrecoverable;
+    /// the context must be recreated.
+    UnknownContextReset,
+}
+
+/// An error produced while creating, recreating, or otherwise managing a GL
+/// context.
+#[derive(Debug, Clone)]
+pub enum ContextError {
+    /// The platform/OS call used to create or manipulate the context failed.
+    OsError(String),
+    /// The context's GL state was lost (see [`ResetStatus`]) and must be
+    /// recreated rather than repaired in place.
+    ContextLost,
+    /// The requested function (such as an adaptive swap interval, or a
+    /// share-context join) isn't supported by this driver or platform.
+    FunctionUnavailable,
+    /// [`GlAttributes::sharing`] named a context whose `PixelFormat` or
+    /// `Api` is incompatible with the one being created, or the driver
+    /// otherwise refused to join its object namespace.
+    SharingIncompatible,
+}
+
+impl std::fmt::Display for ContextError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContextError::OsError(msg) => write!(f, "os error creating GL context: {}", msg),
+            ContextError::ContextLost => write!(f, "GL context was lost and must be recreated"),
+            ContextError::FunctionUnavailable => {
+                write!(f, "requested GL context function is unavailable")
+            }
+            ContextError::SharingIncompatible => {
+                write!(f, "cannot share GL object namespace with an incompatible context")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ContextError {}
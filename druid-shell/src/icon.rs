@@ -1,4 +1,6 @@
-use crate::{backend::window::PlatformIcon, Error};
+use std::fmt;
+
+use crate::backend::window::PlatformIcon;
 
 /// An icon used for the window titlebar, taskbar, etc.
 #[derive(Clone, PartialEq)]
@@ -11,9 +13,175 @@ impl Icon {
     ///
     /// The length of `rgba` must be divisible by 4, and `width * height` must equal
     /// `rgba.len() / 4`. Otherwise, this will return a `BadIcon` error.
-    pub fn from_rgba(rgba: Vec<u8>, width: u32, height: u32) -> Result<Self, Error> {
+    pub fn from_rgba(rgba: Vec<u8>, width: u32, height: u32) -> Result<Self, BadIcon> {
+        Icon::from_rgba_sizes(vec![(rgba, width, height)])
+    }
+
+    /// Creates a multi-resolution `Icon` from several RGBA frames, each at its own size.
+    ///
+    /// Each `(rgba, width, height)` frame is validated the same way as
+    /// [`Icon::from_rgba`]. The platform picks whichever frame most closely matches
+    /// the size it actually needs (titlebar, taskbar, Alt-Tab switcher, ...) instead
+    /// of rescaling a single image for every context.
+    pub fn from_rgba_sizes(frames: Vec<(Vec<u8>, u32, u32)>) -> Result<Self, BadIcon> {
+        for (rgba, width, height) in &frames {
+            validate_rgba_size(rgba, *width, *height)?;
+        }
+
         Ok(Icon {
-            inner: PlatformIcon::from_rgba(rgba, width, height)?,
+            inner: PlatformIcon::from_rgba_sizes(frames).map_err(BadIcon::OsError)?,
         })
     }
+
+    /// Loads an `Icon` by decoding an image file from disk.
+    ///
+    /// The format is sniffed from the file's contents; any format the `image` crate
+    /// supports (PNG, ICO, ...) is accepted. Requires the `image` feature.
+    #[cfg(feature = "image")]
+    pub fn from_file<P: AsRef<std::path::Path>>(path: P) -> Result<Self, BadIcon> {
+        let bytes = std::fs::read(path).map_err(BadIcon::Io)?;
+        Icon::from_file_data(&bytes, None)
+    }
+
+    /// Loads an `Icon` by decoding already-read image bytes.
+    ///
+    /// `format` can be supplied to skip the `image` crate's format sniffing when the
+    /// source format is already known; pass `None` to have it sniffed from `bytes`.
+    /// Requires the `image` feature.
+    #[cfg(feature = "image")]
+    pub fn from_file_data(
+        bytes: &[u8],
+        format: Option<image::ImageFormat>,
+    ) -> Result<Self, BadIcon> {
+        let image = match format {
+            Some(format) => image::load_from_memory_with_format(bytes, format),
+            None => image::load_from_memory(bytes),
+        }
+        .map_err(BadIcon::Decode)?;
+        let rgba = image.to_rgba8();
+        let (width, height) = rgba.dimensions();
+        Icon::from_rgba(rgba.into_raw(), width, height)
+    }
+
+    /// Loads a multi-resolution `Icon` from every frame stored in an `.ico` file.
+    ///
+    /// Requires the `ico` feature.
+    #[cfg(feature = "ico")]
+    pub fn from_ico_file<P: AsRef<std::path::Path>>(path: P) -> Result<Self, BadIcon> {
+        let file = std::fs::File::open(path).map_err(BadIcon::Io)?;
+        let icon_dir = ico::IconDir::read(file).map_err(BadIcon::IcoDecode)?;
+
+        let mut frames = Vec::with_capacity(icon_dir.entries().len());
+        for entry in icon_dir.entries() {
+            let image = entry.decode().map_err(BadIcon::IcoDecode)?;
+            frames.push((image.rgba_data().to_vec(), image.width(), image.height()));
+        }
+
+        Icon::from_rgba_sizes(frames)
+    }
+}
+
+/// Validates a single `(rgba, width, height)` frame the way [`Icon::from_rgba`] and
+/// [`Icon::from_rgba_sizes`] require: `rgba.len()` divisible by 4, and
+/// `width * height` equal to the resulting pixel count.
+fn validate_rgba_size(rgba: &[u8], width: u32, height: u32) -> Result<(), BadIcon> {
+    if rgba.len() % 4 != 0 {
+        return Err(BadIcon::ByteCountNotDivisibleBy4 {
+            byte_count: rgba.len(),
+        });
+    }
+    let pixel_count = rgba.len() / 4;
+    let width_x_height = width as usize * height as usize;
+    if pixel_count != width_x_height {
+        return Err(BadIcon::DimensionsVsPixelCount {
+            width,
+            height,
+            width_x_height,
+            pixel_count,
+        });
+    }
+    Ok(())
+}
+
+/// An error produced when constructing an [`Icon`] from raw RGBA data.
+#[derive(Debug)]
+pub enum BadIcon {
+    /// Produced when the length of the `rgba` argument isn't divisible by 4, despite
+    /// every pixel requiring 4 bytes (red, green, blue, alpha).
+    ByteCountNotDivisibleBy4 {
+        /// The length of the provided RGBA buffer.
+        byte_count: usize,
+    },
+    /// Produced when the number of pixels implied by `width * height` doesn't match
+    /// the number of pixels supplied in the `rgba` argument.
+    DimensionsVsPixelCount {
+        /// The specified width.
+        width: u32,
+        /// The specified height.
+        height: u32,
+        /// `width * height`, as pixels.
+        width_x_height: usize,
+        /// The number of pixels in the provided RGBA buffer (`rgba.len() / 4`).
+        pixel_count: usize,
+    },
+    /// Produced when the underlying platform failed to create the icon.
+    OsError(std::io::Error),
+    /// Produced when [`Icon::from_file`] or [`Icon::from_ico_file`] couldn't read
+    /// the icon file from disk. Requires the `image` or `ico` feature.
+    #[cfg(any(feature = "image", feature = "ico"))]
+    Io(std::io::Error),
+    /// Produced when the `image` crate failed to decode the icon data.
+    /// Requires the `image` feature.
+    #[cfg(feature = "image")]
+    Decode(image::ImageError),
+    /// Produced when the `ico` crate failed to decode an `.ico` file's frames.
+    /// Requires the `ico` feature.
+    #[cfg(feature = "ico")]
+    IcoDecode(ico::IcoError),
+}
+
+impl fmt::Display for BadIcon {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BadIcon::ByteCountNotDivisibleBy4 { byte_count } => write!(
+                f,
+                "The length of the `rgba` argument ({}) isn't divisible by 4, despite \
+                 the `rgba` argument holding RGBA data (4 bytes per pixel)",
+                byte_count,
+            ),
+            BadIcon::DimensionsVsPixelCount {
+                width,
+                height,
+                width_x_height,
+                pixel_count,
+            } => write!(
+                f,
+                "The specified dimensions ({}x{} => {} pixels) don't match the number of \
+                 pixels supplied by the `rgba` argument ({} pixels)",
+                width, height, width_x_height, pixel_count,
+            ),
+            BadIcon::OsError(err) => write!(f, "OS error when constructing the icon: {}", err),
+            #[cfg(any(feature = "image", feature = "ico"))]
+            BadIcon::Io(err) => write!(f, "Failed to read the icon file: {}", err),
+            #[cfg(feature = "image")]
+            BadIcon::Decode(err) => write!(f, "Failed to decode the icon data: {}", err),
+            #[cfg(feature = "ico")]
+            BadIcon::IcoDecode(err) => write!(f, "Failed to decode the .ico file: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for BadIcon {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BadIcon::OsError(err) => Some(err),
+            #[cfg(any(feature = "image", feature = "ico"))]
+            BadIcon::Io(err) => Some(err),
+            #[cfg(feature = "image")]
+            BadIcon::Decode(err) => Some(err),
+            #[cfg(feature = "ico")]
+            BadIcon::IcoDecode(err) => Some(err),
+            _ => None,
+        }
+    }
 }